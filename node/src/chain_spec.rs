@@ -1,8 +1,8 @@
 use sp_core::{Pair, Public, sr25519};
 use zcloak_runtime::{
 	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig,
-	SudoConfig, SystemConfig, SessionConfig, ValidatorSetConfig, 
-	WASM_BINARY, Signature, SessionKeys, VerifierId
+	SudoConfig, SystemConfig, SessionConfig, ValidatorSetConfig, StarksVerifierConfig,
+	WASM_BINARY, Signature, SessionKeys, VerifierId, TaskInfo, Backend, Perbill,
 };
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_finality_grandpa::AuthorityId as GrandpaId;
@@ -136,6 +136,13 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 	))
 }
 
+/// The class and the program of the sample task seeded into the test networks.
+const DEMO_CLASS: &[u8] = b"demo";
+const DEMO_PROGRAM_HASH: [u8; 32] = [
+	19, 23, 145, 150, 7, 226, 183, 94, 42, 36, 220, 169, 148, 89, 125, 153,
+	113, 250, 202, 142, 187, 167, 14, 144, 186, 217, 89, 214, 222, 234, 43, 214,
+];
+
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
@@ -168,6 +175,24 @@ fn testnet_genesis(
 		pallet_grandpa: GrandpaConfig {
 			authorities: vec![],
 		},
+		pallet_starks_verifier: StarksVerifierConfig {
+			// The verifier keys are provided by the session module.
+			keys: vec![],
+			classes: vec![(DEMO_CLASS.to_vec(), root_key.clone(), DEMO_PROGRAM_HASH)],
+			threshold: Perbill::from_percent(50),
+			settled_tasks: vec![(
+				root_key.clone(),
+				DEMO_CLASS.to_vec(),
+				TaskInfo {
+					proof_id: b"QmSmn1rSSXmu1PyFFTosBtcL2KGzEssetk9MVFYyDHoCGa".to_vec(),
+					inputs: vec![1, 0],
+					outputs: vec![8],
+					program_hash: DEMO_PROGRAM_HASH,
+					backend: Backend::Distaff,
+				},
+				true,
+			)],
+		},
 		pallet_sudo: SudoConfig {
 			// Assign network admin rights.
 			key: root_key,
@@ -8,6 +8,11 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// Keep a local store of proofs in this directory, served by the `verifier_putProof` and
+	/// `verifier_getProof` RPC and read by the offchain workers before IPFS.
+	#[structopt(long, parse(from_os_str), value_name = "PATH")]
+	pub proof_store: Option<std::path::PathBuf>,
 }
 
 #[derive(Debug, StructOpt)]
@@ -35,6 +40,22 @@ pub enum Subcommand {
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Compute the hash of a distaff program from its assembly source, or check it.
+	#[structopt(name = "program-hash")]
+	ProgramHash(crate::starks::ProgramHashCmd),
+
+	/// Verify a proof natively, as the offchain workers do.
+	#[structopt(name = "verify-proof")]
+	VerifyProof(crate::starks::VerifyProofCmd),
+
+	/// Prove the execution of a distaff program, for a task to be created with the proof.
+	#[structopt(name = "prove")]
+	Prove(crate::starks::ProveCmd),
+
+	/// Decode a proof and print its structure, without verifying it.
+	#[structopt(name = "inspect-proof")]
+	InspectProof(crate::starks::InspectProofCmd),
+
 	/// The custom benchmark subcommmand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
@@ -115,6 +115,10 @@ pub fn run() -> sc_cli::Result<()> {
 				Ok((cmd.run(client, backend), task_manager))
 			})
 		},
+		Some(Subcommand::ProgramHash(cmd)) => cmd.run(),
+		Some(Subcommand::VerifyProof(cmd)) => cmd.run(),
+		Some(Subcommand::Prove(cmd)) => cmd.run(),
+		Some(Subcommand::InspectProof(cmd)) => cmd.run(),
 		Some(Subcommand::Benchmark(cmd)) => {
 			if cfg!(feature = "runtime-benchmarks") {
 				let runner = cli.create_runner(cmd)?;
@@ -126,6 +130,9 @@ pub fn run() -> sc_cli::Result<()> {
 			}
 		},
 		None => {
+			if let Some(dir) = &cli.proof_store {
+				sp_starks::proof_store::open(dir)?;
+			}
 			let runner = cli.create_runner(&cli.run)?;
 			runner.run_node_until_exit(|config| async move {
 				match config.role {
@@ -13,8 +13,56 @@ use sp_blockchain::{Error as BlockChainError, HeaderMetadata, HeaderBackend};
 use sp_block_builder::BlockBuilder;
 pub use sc_rpc_api::DenyUnsafe;
 use sp_transaction_pool::TransactionPool;
+use sp_core::Bytes;
+use sp_starks::proof_store::ProofStore;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result as RpcResult};
+use jsonrpc_derive::rpc;
 
 
+/// Upload and read the proofs of the local proof store of the node.
+#[rpc]
+pub trait ProofStoreApi {
+	/// Store a proof, returning the id to create a task with.
+	#[rpc(name = "verifier_putProof")]
+	fn put_proof(&self, proof: Bytes) -> RpcResult<String>;
+
+	/// The proof stored under `id`, if any.
+	#[rpc(name = "verifier_getProof")]
+	fn get_proof(&self, id: String) -> RpcResult<Option<Bytes>>;
+}
+
+/// The `ProofStoreApi` served from a `ProofStore`.
+pub struct ProofStoreRpc {
+	store: &'static ProofStore,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl ProofStoreRpc {
+	/// Serve `store`. Proofs are only uploaded through the unsafe RPC interface.
+	pub fn new(store: &'static ProofStore, deny_unsafe: DenyUnsafe) -> Self {
+		Self { store, deny_unsafe }
+	}
+}
+
+fn store_error(e: std::io::Error) -> RpcError {
+	RpcError {
+		code: ErrorCode::InternalError,
+		message: "Proof store error".into(),
+		data: Some(e.to_string().into()),
+	}
+}
+
+impl ProofStoreApi for ProofStoreRpc {
+	fn put_proof(&self, proof: Bytes) -> RpcResult<String> {
+		self.deny_unsafe.check_if_safe()?;
+		self.store.put(&proof).map_err(store_error)
+	}
+
+	fn get_proof(&self, id: String) -> RpcResult<Option<Bytes>> {
+		self.store.get(&id).map(|proof| proof.map(Into::into)).map_err(store_error)
+	}
+}
+
 /// Full client dependencies.
 pub struct FullDeps<C, P> {
 	/// The client instance to use.
@@ -55,6 +103,12 @@ pub fn create_full<C, P>(
 		TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone()))
 	);
 
+	if let Some(store) = sp_starks::proof_store::store() {
+		io.extend_with(
+			ProofStoreApi::to_delegate(ProofStoreRpc::new(store, deny_unsafe))
+		);
+	}
+
 	// Extend this RPC with a custom API by using the following syntax.
 	// `YourRpcStruct` should have a reference to a client, which is needed
 	// to call into the runtime.
@@ -166,6 +166,9 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 		sc_service::build_offchain_workers(
 			&config, task_manager.spawn_handle(), client.clone(), network.clone(),
 		);
+		if let Some(registry) = config.prometheus_registry() {
+			sp_starks::metrics::register_metrics(registry)?;
+		}
 	}
 
 	let role = config.role.clone();
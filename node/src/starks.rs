@@ -0,0 +1,272 @@
+//! Subcommands working with distaff programs and proofs, without running a node.
+
+use std::{fs, path::{Path, PathBuf}, time::Instant};
+use structopt::StructOpt;
+use sp_starks::{Backend, VerifyError};
+use sp_state_machine::BasicExternalities;
+
+/// The `program-hash` command
+#[derive(Debug, StructOpt)]
+pub struct ProgramHashCmd {
+	/// Path to the distaff assembly source of the program.
+	#[structopt(parse(from_os_str))]
+	pub source: PathBuf,
+
+	/// Check the program against this hash, hex encoded, instead of printing its hash.
+	#[structopt(long, value_name = "HASH")]
+	pub check: Option<String>,
+}
+
+impl ProgramHashCmd {
+	/// Run the command
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let source = fs::read_to_string(&self.source)?;
+		match &self.check {
+			None => println!("0x{}", hex::encode(sp_starks::program::program_hash(&source)?)),
+			Some(expected) => {
+				if !sp_starks::program::check_program_hash(&source, &decode_hash(expected)?)? {
+					return Err("Program hash mismatch, run without --check to print the hash of the source".into());
+				}
+				println!("Program hash matches");
+			},
+		}
+		Ok(())
+	}
+}
+
+/// The `verify-proof` command
+#[derive(Debug, StructOpt)]
+pub struct VerifyProofCmd {
+	/// The hash of the program, hex encoded.
+	#[structopt(long, value_name = "HASH")]
+	pub program_hash: String,
+
+	/// The public inputs of the program, separated by commas.
+	#[structopt(long, use_delimiter = true, value_name = "INPUTS")]
+	pub inputs: Vec<u128>,
+
+	/// The outputs of the program, separated by commas.
+	#[structopt(long, use_delimiter = true, value_name = "OUTPUTS")]
+	pub outputs: Vec<u128>,
+
+	/// The backend the proof is generated with.
+	#[structopt(long, default_value = "distaff", possible_values = &["distaff", "miden"])]
+	pub backend: String,
+
+	/// Path to the proof, either raw as serialized by the prover, or hex encoded.
+	#[structopt(parse(from_os_str))]
+	pub proof: PathBuf,
+}
+
+impl VerifyProofCmd {
+	/// Run the command
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let program_hash = decode_hash(&self.program_hash)?;
+		let backend = parse_backend(&self.backend)?;
+		let proof = read_proof(&self.proof)?;
+		println!("Verifying a proof of {} bytes with {:?}", proof.len(), backend);
+
+		// The host functions of `Starks` run against externalities, even if they do not use them.
+		let started = Instant::now();
+		let result = BasicExternalities::default().execute_with(|| {
+			sp_starks::starks::verify(backend, &program_hash, &self.inputs, &self.outputs, &proof)
+		});
+		let elapsed = started.elapsed();
+
+		match result {
+			Ok(true) => println!("Proof is valid"),
+			Ok(false) => println!("Proof is NOT valid"),
+			Err(VerifyError::MalformedProof) => println!("Proof can not be decoded"),
+			Err(VerifyError::InvalidProof) => println!("Proof does not prove the program with these inputs and outputs"),
+			Err(VerifyError::UnsupportedVersion) => println!("Backend {:?} is not supported by this node", backend),
+			Err(VerifyError::ResourceLimit) => println!("Proof is larger than {} bytes", sp_starks::MAX_PROOF_SIZE),
+		}
+		println!("Verified in {} ms", elapsed.as_millis());
+
+		if result != Ok(true) {
+			return Err("Verification failed".into());
+		}
+		Ok(())
+	}
+}
+
+/// The `prove` command
+#[derive(Debug, StructOpt)]
+pub struct ProveCmd {
+	/// Path to the distaff assembly source of the program.
+	#[structopt(parse(from_os_str))]
+	pub source: PathBuf,
+
+	/// The public inputs of the program, separated by commas.
+	#[structopt(long, use_delimiter = true, value_name = "INPUTS")]
+	pub inputs: Vec<u128>,
+
+	/// The secret inputs of the program on tape A, separated by commas.
+	#[structopt(long, use_delimiter = true, value_name = "INPUTS")]
+	pub secret_a: Vec<u128>,
+
+	/// The secret inputs of the program on tape B, separated by commas.
+	#[structopt(long, use_delimiter = true, value_name = "INPUTS")]
+	pub secret_b: Vec<u128>,
+
+	/// The number of outputs to read from the stack.
+	#[structopt(long, default_value = "1")]
+	pub num_outputs: usize,
+
+	/// The blowup factor of the execution trace.
+	#[structopt(long, default_value = "32")]
+	pub extension_factor: usize,
+
+	/// The number of queries of the proof.
+	#[structopt(long, default_value = "48")]
+	pub num_queries: usize,
+
+	/// The number of bits of proof-of-work.
+	#[structopt(long, default_value = "0")]
+	pub grinding_factor: u32,
+
+	/// Where to write the proof, serialized with bincode.
+	#[structopt(long, short, parse(from_os_str), default_value = "proof.bin")]
+	pub output: PathBuf,
+
+	/// Also publish the proof to the IPFS API at this url, the store the offchain workers fetch
+	/// proofs from, and print its id.
+	#[structopt(long, value_name = "URL")]
+	pub publish: Option<String>,
+}
+
+impl ProveCmd {
+	/// Run the command
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let source = fs::read_to_string(&self.source)?;
+		let program = sp_starks::program::compile(&source)?;
+		let options = sp_starks::program::proof_options(self.extension_factor, self.num_queries, self.grinding_factor);
+
+		let started = Instant::now();
+		let (outputs, proof) = sp_starks::program::prove(
+			&program,
+			&self.inputs,
+			(&self.secret_a, &self.secret_b),
+			self.num_outputs,
+			&options,
+		)?;
+		eprintln!("Proved in {} ms, the proof is {} bytes", started.elapsed().as_millis(), proof.len());
+
+		fs::write(&self.output, &proof)?;
+		println!("program hash: 0x{}", hex::encode(program.hash()));
+		println!("inputs: {:?}", self.inputs);
+		println!("outputs: {:?}", outputs);
+		println!("proof: {}", self.output.display());
+
+		if let Some(url) = &self.publish {
+			println!("proof id: {}", publish_proof(url, &proof)?);
+		}
+		Ok(())
+	}
+}
+
+/// The `inspect-proof` command
+#[derive(Debug, StructOpt)]
+pub struct InspectProofCmd {
+	/// The backend the proof is generated with.
+	#[structopt(long, default_value = "distaff", possible_values = &["distaff", "miden"])]
+	pub backend: String,
+
+	/// Path to the proof, either raw as serialized by the prover, or hex encoded.
+	#[structopt(parse(from_os_str))]
+	pub proof: PathBuf,
+}
+
+impl InspectProofCmd {
+	/// Run the command
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let backend = parse_backend(&self.backend)?;
+		let proof = read_proof(&self.proof)?;
+		let summary = sp_starks::inspect::inspect(backend, &proof)
+			.map_err(|e| format!("The proof can not be decoded with {:?}: {:?}", backend, e))?;
+
+		println!("backend: {:?}", summary.backend);
+		println!("size: {} bytes", summary.size);
+		println!("  trace queries: {} bytes", summary.trace_queries_size);
+		println!("  constraint queries: {} bytes", summary.constraint_queries_size);
+		println!("  degree proof: {} bytes", summary.degree_proof_size);
+		println!("trace length: {}", summary.trace_length);
+		println!("stack depth: {}", summary.stack_depth);
+		println!("extension factor: {}", summary.extension_factor);
+		println!("queries: {}", summary.num_queries);
+		println!("grinding factor: {}", summary.grinding_factor);
+		println!("security level: {} bits", summary.security_level);
+		match summary.program_hash {
+			Some(hash) => println!("program hash: 0x{}", hex::encode(hash)),
+			None => println!("program hash: not embedded in the proof"),
+		}
+
+		if !summary.malformations.is_empty() {
+			for malformation in &summary.malformations {
+				println!("malformed: {}", malformation);
+			}
+			return Err("The proof is not well formed".into());
+		}
+		println!("The proof is well formed");
+		Ok(())
+	}
+}
+
+/// Add `proof` to the IPFS node whose API is at `url`, returning its id.
+pub fn publish_proof(url: &str, proof: &[u8]) -> sc_cli::Result<String> {
+	const BOUNDARY: &str = "----starks-proof-boundary";
+	let mut body = format!(
+		"--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"proof\"\r\nContent-Type: application/octet-stream\r\n\r\n",
+		BOUNDARY,
+	).into_bytes();
+	body.extend_from_slice(proof);
+	body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+
+	let response = ureq::post(&format!("{}/api/v0/add", url.trim_end_matches('/')))
+		.set("Content-Type", &format!("multipart/form-data; boundary={}", BOUNDARY))
+		.send_bytes(&body);
+	if let Some(e) = response.synthetic_error() {
+		return Err(format!("Failed to publish the proof: {}", e).into());
+	}
+	if !response.ok() {
+		return Err(format!("Failed to publish the proof: {} {}", response.status(), response.status_text()).into());
+	}
+	let response = response.into_json()
+		.map_err(|e| format!("Invalid response of the proof store: {}", e))?;
+	response["Hash"].as_str()
+		.map(ToString::to_string)
+		.ok_or_else(|| "The proof store did not return the id of the proof".into())
+}
+
+/// Parse the name of a backend.
+pub fn parse_backend(backend: &str) -> sc_cli::Result<Backend> {
+	match backend {
+		"distaff" => Ok(Backend::Distaff),
+		"miden" => Ok(Backend::Miden),
+		_ => Err(format!("Unknown backend {}", backend).into()),
+	}
+}
+
+/// Read a proof from `path`, decoding it if it is hex encoded.
+pub fn read_proof(path: &Path) -> sc_cli::Result<Vec<u8>> {
+	let raw = fs::read(path)?;
+	let hex_encoded = std::str::from_utf8(&raw).ok()
+		.map(|text| text.trim().trim_start_matches("0x"))
+		.filter(|text| !text.is_empty() && text.len() % 2 == 0 && text.bytes().all(|b| b.is_ascii_hexdigit()));
+	match hex_encoded {
+		Some(text) => hex::decode(text).map_err(|e| format!("Invalid hex proof: {}", e).into()),
+		None => Ok(raw),
+	}
+}
+
+/// Decode a 32-byte hash from hex, with or without the `0x` prefix.
+pub fn decode_hash(hash: &str) -> sc_cli::Result<[u8; 32]> {
+	let bytes = hex::decode(hash.trim_start_matches("0x"))
+		.map_err(|e| format!("Invalid hash {}: {}", hash, e))?;
+	if bytes.len() != 32 {
+		return Err(format!("Invalid hash {}: expected 32 bytes, got {}", hash, bytes.len()).into());
+	}
+	let mut decoded = [0u8; 32];
+	decoded.copy_from_slice(&bytes);
+	Ok(decoded)
+}
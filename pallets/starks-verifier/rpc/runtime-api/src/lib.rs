@@ -0,0 +1,17 @@
+//! Runtime API definition for the starks-verifier module.
+
+// Ensure we're `no_std` when compiling for Wasm.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+
+sp_api::decl_runtime_apis! {
+    /// The API to query the state of the starks verifier.
+    pub trait StarksVerifierApi<AccountId, Balance> where
+        AccountId: Codec,
+        Balance: Codec,
+    {
+        /// The rewards credited to `who` which have not been claimed yet.
+        fn pending_rewards(who: AccountId) -> Balance;
+    }
+}
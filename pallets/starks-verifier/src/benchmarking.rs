@@ -0,0 +1,65 @@
+//! Benchmarks of the Starks-verifier Module
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{benchmarks, whitelisted_caller, impl_benchmark_test_suite};
+use frame_system::RawOrigin;
+
+// A distaff proof of the program of `PROGRAM_HASH`, with the inputs `[1, 0]` and the outputs `[8]`
+const PROOF: &[u8] = include_bytes!("../proof.txt");
+const PROGRAM_HASH: [u8; 32] = [
+	19, 23, 145, 150, 7, 226, 183, 94, 42, 36, 220, 169, 148, 89, 125, 153,
+	113, 250, 202, 142, 187, 167, 14, 144, 186, 217, 89, 214, 222, 234, 43, 214,
+];
+
+/// A proof `verify_on_chain` is benchmarked with: the proof, the hash of its program, its inputs
+/// and its outputs.
+type OnChainProof = (&'static [u8], [u8; 32], &'static [u128], &'static [u128]);
+
+// The proofs `verify_on_chain` is benchmarked with, by increasing size. Their cost grows with the
+// trace length and the number of queries they are generated with, and so does their size, so the
+// runtime does not accept proofs larger than the last one. More are generated with `prove`.
+const ON_CHAIN_PROOFS: &[OnChainProof] = &[
+	(PROOF, PROGRAM_HASH, &[1, 0], &[8]),
+];
+
+/// The largest of `ON_CHAIN_PROOFS` no larger than `size` bytes, or the smallest one.
+fn on_chain_proof(size: u32) -> &'static OnChainProof {
+	ON_CHAIN_PROOFS.iter()
+		.rev()
+		.find(|(proof, ..)| proof.len() as u32 <= size)
+		.unwrap_or(&ON_CHAIN_PROOFS[0])
+}
+
+benchmarks! {
+	// The proof is only indexed, so its content does not matter, but its size does.
+	create_task {
+		let s in 0 .. T::MaxIndexedProofSize::get();
+		let caller: T::AccountId = whitelisted_caller();
+		T::Currency::make_free_balance_be(&caller, T::TaskFee::get() * 10u32.into());
+		let class = b"benchmark".to_vec();
+		let proof = vec![0u8; s as usize];
+	}: _(RawOrigin::Signed(caller.clone()), class.clone(), PROGRAM_HASH, Backend::Distaff, vec![1, 0], vec![8], b"proof".to_vec(), Some(proof), None)
+	verify {
+		assert!(OngoingTasks::<T>::contains_key(&caller, &class));
+	}
+
+	// Every step verifies a real proof of at most `s` bytes, since a padded one would be rejected
+	// before most of its verification.
+	verify_on_chain {
+		let s in 0 .. T::MaxOnChainProofSize::get();
+		let caller: T::AccountId = whitelisted_caller();
+		let class = b"benchmark".to_vec();
+		let (proof, program_hash, inputs, outputs) = on_chain_proof(s);
+	}: _(RawOrigin::Signed(caller.clone()), class.clone(), *program_hash, Backend::Distaff, inputs.to_vec(), outputs.to_vec(), proof.to_vec())
+	verify {
+		assert_eq!(Attestations::<T>::get(&caller, &class).map(|attestation| attestation.passed), Some(true));
+	}
+}
+
+impl_benchmark_test_suite!(
+	Pallet,
+	crate::mock::new_test_ext(),
+	crate::mock::Test,
+);
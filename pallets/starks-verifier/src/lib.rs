@@ -17,10 +17,66 @@
 //! ### Dispatchable Functions
 //!
 //! * `create_task` - Create a task with program_has h,inputs, outputs, proof_id.
+//! * `create_task_for` - Create a task on behalf of an account which has authorized it off-chain.
+//! * `create_bundle` - Create a task made of several programs, which passes only if all of them pass.
+//! * `verify_on_chain` - Verify a small proof in the runtime and settle its task at once.
+//! * `claim_rewards` - Claim the rewards earned by voting for settled tasks.
+//! * `set_quota_exempt` - Exempt an account from the quotas on task creation.
+//! * `register_class` - Register a class proved by a given program, owned by the caller.
+//! * `set_class_validity` - Set how long the attestations of a class are valid.
+//! * `revoke_attestation` - Revoke the attestation of an account for a class.
 //! * `offchain_worker` - For validated offchain-workers to dispatch only,in order to 
 //! verify tasks.
-//! * `on_finalize` - Remove SettledTask which is expired at this block
+//! * `on_finalize` - Remove SettledTask which is expired at this block, along with its task
 //!
+//! ### Rewards
+//!
+//! Every task pays a `TaskFee` into the reward pool of this module. Once the task is settled,
+//! the fee is split evenly among the verifiers whose vote matched the outcome and credited
+//! to their `PendingRewards`, which can be withdrawn with `claim_rewards`.
+//!
+//! A creator can add a priority fee on top of the `TaskFee`. Verifiers pick the tasks with the
+//! highest priority fee first, then the oldest ones, and the priority fee is shared with the
+//! `TaskFee`.
+//!
+//! ### Quotas
+//!
+//! The tasks an account or a class can have open at the same time are capped, and so are the
+//! tasks created in every window of `QuotaWindow` blocks. Trusted issuers onboarding many users
+//! at once can be exempted by root, and so are the tasks they pay for with `create_task_for`.
+//!
+//! ### Bundles
+//!
+//! A bundle binds the proofs of several programs, e.g. of age and of residency, to the same
+//! subject. It is voted on and settled as a single task, whose program hash is the `bundle_hash`
+//! of its components, and it only passes if every component does.
+//!
+//! ### Delegated tasks
+//!
+//! An account holding no funds can sign a `TaskAuthorization` off-chain and hand it to a relayer,
+//! which submits it with `create_task_for` and pays the fees. The task and its attestation belong
+//! to the signer. Every authorization carries the next nonce of the signer, so that it can only
+//! be used once, and the block number after which it can not be used anymore. The signed payload
+//! includes the genesis hash, so that an authorization is only valid on the chain it is meant for.
+//!
+//! ### On-chain verification
+//!
+//! A proof no larger than `MaxOnChainProofSize` can be verified by the runtime itself with
+//! `verify_on_chain`. The task is settled in the same block, without waiting for the votes of the
+//! verifiers, and no `TaskFee` is paid since the weight of the call covers the verification.
+//!
+//! ### Security levels
+//!
+//! A proof claims the security level of the options it is generated with. Verifiers reject the
+//! proofs below `MinSecurityLevel`, or below the level the owner of their class asks for if it is
+//! higher, and the tasks rejected for that reason are reported by `TaskRejectedInsecure`.
+//!
+//! ### Attestations
+//!
+//! A settled task leaves an attestation, which expires after `StorePeriod` unless the owner of
+//! its class has set a validity period for it. An attestation can be revoked before it expires,
+//! by the owner of its class or by the account which created the task. Its subject can then
+//! prove the class again without waiting for it to expire.
 //! 
 //! 
 // Ensure we're `no_std` when compiling for Wasm.
@@ -31,6 +87,7 @@ use codec::{Encode, Decode};
 use sp_std::prelude::*;
 use sp_std::{
     borrow::ToOwned,
+    cmp::Reverse,
     iter::FromIterator,
     collections::btree_set::BTreeSet,
     convert::From,
@@ -44,19 +101,38 @@ use sp_runtime::{
     },
 };
 use sp_core::crypto::KeyTypeId;
+use sp_staking::SessionIndex;
+use sp_runtime::traits::{AccountIdConversion, IdentifyAccount, Saturating, Verify, Zero};
+use sp_runtime::{Perbill, PerThing};
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
 use frame_support::{
     dispatch::DispatchResult,
     decl_module, decl_event, decl_storage, Parameter, debug, decl_error, ensure,
-    traits::{EstimateNextSessionRotation, Get, OneSessionHandler, ValidatorSet,
-		ValidatorSetWithIdentification},
+    traits::{Currency, EstimateNextSessionRotation, ExistenceRequirement, Get, OneSessionHandler,
+        ValidatorSet, ValidatorSetWithIdentification},
+    PalletId,
+    transactional,
 };
-use frame_system::{ensure_signed, ensure_none};
+use frame_system::{ensure_signed, ensure_none, ensure_root};
 use frame_system::offchain::{
     SendTransactionTypes,
     SubmitTransaction,
 };
 pub use pallet::*;
 
+pub mod migrations;
+pub mod traits;
+pub mod weights;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub use traits::{OnTaskSettled, OnTaskRejected, VerificationProvider};
+pub use sp_starks::{Backend, VerifyError};
+use sp_starks::WorkerEvent;
+pub use weights::WeightInfo;
+
 #[cfg(all(feature = "std", test))]
 mod mock;
 
@@ -87,9 +163,9 @@ pub mod crypto {
 
 /// The status of a given verification task
 #[derive(Encode, Decode, Default, PartialEq, Eq, RuntimeDebug)]
-pub struct Status {
-    // The verifiers involved so far
-    pub verifiers: Vec<u32>,
+pub struct Status<AccountId> {
+    // The accounts of the verifiers involved so far, whichever key they have signed with
+    pub verifiers: Vec<AccountId>,
     // The number of affirmative vote so far
     pub ayes: u32,
     // The number of dissenting vote so far
@@ -104,29 +180,135 @@ pub struct VerificationReceipt<AccountId, BlockNumber> {
     program_hash: [u8; 32],
     // Whether a task is passed or not
     passed: bool,
+    // Whether the proof is below the minimum security level of its class, counted as a nay
+    insecure: bool,
     // Block number at the time submission is created.
     submit_at: BlockNumber,
-    // Submitted by who
+    // Submitted by who, as an index into the key set of `session_index`
     auth_index: u32,
-    // The length of session validator set
-    validators_len: u32
+    // The session in which the receipt is signed
+    session_index: SessionIndex
 }
 
 /// Info of a certain task
-#[derive(Encode, Decode, Default, PartialEq, Eq, RuntimeDebug)]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub struct TaskInfo {
     // The id of the proof,combined with a url to fetch the complete proof later
-    proof_id: Vec<u8>,
+    pub proof_id: Vec<u8>,
     // Inputs of the task 
-    inputs: Vec<u128>,
+    pub inputs: Vec<u128>,
     // Outputs of the task
-    outputs: Vec<u128>,
+    pub outputs: Vec<u128>,
     // The hash of the program
-    program_hash: [u8; 32]
+    pub program_hash: [u8; 32],
+    // The proof system the program is proved with
+    pub backend: Backend,
+}
+
+/// Info of a registered class
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ClassInfo<AccountId> {
+    // The owner of the class
+    pub owner: AccountId,
+    // The hash of the program which every task of the class has to run
+    pub program_hash: [u8; 32],
+}
+
+/// The tasks counted against the quotas of an account or a class
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Quota<BlockNumber> {
+    // The number of tasks which are not settled yet
+    pub open: u32,
+    // The first block of the window `created` is counted in
+    pub window_start: BlockNumber,
+    // The number of tasks created since `window_start`
+    pub created: u32,
+}
+
+impl<BlockNumber: PartialEq> Quota<BlockNumber> {
+    /// Start counting the created tasks afresh if `window_start` is a new window.
+    fn at(mut self, window_start: BlockNumber) -> Self {
+        if self.window_start != window_start {
+            self.window_start = window_start;
+            self.created = 0;
+        }
+        self
+    }
+
+    /// Whether one more task fits in the quota.
+    fn within(&self, max_open: u32, max_created: u32) -> bool {
+        self.open < max_open && self.created < max_created
+    }
+
+    fn open(&mut self) {
+        self.open = self.open.saturating_add(1);
+        self.created = self.created.saturating_add(1);
+    }
+
+    fn close(&mut self) {
+        self.open = self.open.saturating_sub(1);
+    }
+}
+
+/// The attestation produced by a settled task
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct Attestation<BlockNumber> {
+    // The hash of the program which is proved
+    pub program_hash: [u8; 32],
+    // Whether the task is passed or not
+    pub passed: bool,
+    // Block number at which the task is settled
+    pub settled_at: BlockNumber,
+    // Block number at which the attestation expires and is removed
+    pub expires_at: BlockNumber,
+}
+
+/// A task authorized off-chain by its subject, to be created by a relayer
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct TaskAuthorization<BlockNumber> {
+    // The class of the task
+    pub class: Class,
+    // The hash of the program
+    pub program_hash: [u8; 32],
+    // The proof system the program is proved with
+    pub backend: Backend,
+    // Inputs of the task
+    pub inputs: Vec<u128>,
+    // Outputs of the task
+    pub outputs: Vec<u128>,
+    // The id of the proof
+    pub proof_id: Vec<u8>,
+    // The nonce of the subject, in `AuthorizationNonces`
+    pub nonce: u64,
+    // Block number after which the authorization can not be used
+    pub valid_until: BlockNumber,
 }
 
 /// Class of the privacy in raw
-type Class = Vec<u8>;
+pub type Class = Vec<u8>;
+
+/// The layout of the storage of this module, bumped whenever a stored type changes.
+/// See `migrations` for how the storage is moved from one release to the next.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+pub enum Releases {
+    /// The storage before it was versioned
+    V1,
+    /// Votes are recorded by the account of the verifier, every task declares its `Backend`,
+    /// and settled tasks leave an attestation
+    V2,
+}
+
+impl Default for Releases {
+    fn default() -> Self {
+        Releases::V1
+    }
+}
+
+impl Releases {
+    /// The release of the storage layout used by this version of the module
+    pub const LATEST: Releases = Releases::V2;
+}
 
 /// Error which may occur while executing the off-chain code.
 #[cfg_attr(test, derive(PartialEq))]
@@ -137,7 +319,7 @@ pub enum OffchainErr<BlockNumber> {
     FailedToFetchProof,
     FailedSigning,
     SubmitTransaction(BlockNumber),
-    VerificationFailed,
+    VerificationFailed(VerifyError),
 }
 
 impl<BlockNumber: sp_std::fmt::Debug> sp_std::fmt::Debug for OffchainErr<BlockNumber> {
@@ -150,13 +332,66 @@ impl<BlockNumber: sp_std::fmt::Debug> sp_std::fmt::Debug for OffchainErr<BlockNu
             OffchainErr::FailedSigning => write!(fmt, "Failed to sign the result"),
             OffchainErr::SubmitTransaction(ref now) =>
                 write!(fmt, "Failed to submit transaction at block {:?}", now),
-            OffchainErr::VerificationFailed => write!(fmt, "Failed to verify"),
+            OffchainErr::VerificationFailed(ref error) => write!(fmt, "Failed to verify: {:?}", error),
         }
     }
 }
 
 
 const DB_PREFIX: &[u8] = b"starksnetwork/verification-tasks/";
+const CACHE_DB_KEY: &[u8] = b"starksnetwork/verification-cache";
+/// The number of verdicts kept in the `VerificationCache`
+const CACHE_SIZE: usize = 256;
+/// The prefix of the keys the proofs given to `create_task` are indexed under in the offchain DB
+const PROOF_INDEX_PREFIX: &[u8] = b"starksnetwork/indexed-proofs/";
+
+type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// The outcome of the verification of a task by an offchain worker
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+enum Verdict {
+    Passed,
+    Failed,
+    Insecure,
+}
+
+/// The verdicts of the proofs verified lately by an offchain worker, so that a proof submitted
+/// again for the same program, inputs and outputs is not verified again.
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, RuntimeDebug)]
+struct VerificationCache {
+    // The verdicts by their `cache_key`, the most recently used first
+    entries: Vec<([u8; 32], Verdict)>,
+    // The number of lookups which found a verdict
+    hits: u64,
+    // The number of lookups which did not
+    misses: u64,
+}
+
+impl VerificationCache {
+    /// The verdict cached for `key`, which becomes the most recently used one.
+    fn lookup(&mut self, key: &[u8; 32]) -> Option<Verdict> {
+        match self.entries.iter().position(|(cached, _)| cached == key) {
+            Some(index) => {
+                let entry = self.entries.remove(index);
+                self.entries.insert(0, entry);
+                self.hits = self.hits.saturating_add(1);
+                Some(entry.1)
+            },
+            None => {
+                self.misses = self.misses.saturating_add(1);
+                None
+            },
+        }
+    }
+
+    /// Cache `verdict` for `key`, evicting the least recently used verdict if the cache is full.
+    fn insert(&mut self, key: [u8; 32], verdict: Verdict) {
+        self.entries.retain(|(cached, _)| cached != &key);
+        self.entries.insert(0, (key, verdict));
+        self.entries.truncate(CACHE_SIZE);
+    }
+}
 
 pub type OffchainResult<T, A> = Result<A, OffchainErr<<T as frame_system::Config>::BlockNumber>>;
 
@@ -177,7 +412,29 @@ pub mod pallet {
         type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 
         /// A type for retrieving the validators supposed to be online in a session.
-    	// type ValidatorSet: ValidatorSetWithIdentification<Self::AccountId>;
+        type ValidatorSet: ValidatorSet<Self::AccountId, ValidatorId = Self::AccountId>;
+
+        /// The number of past sessions whose key sets are kept, so that receipts signed
+        /// shortly before a session rotation can still be included.
+        #[pallet::constant]
+        type HistoryDepth: Get<SessionIndex>;
+
+        /// The currency in which task fees and verifier rewards are paid.
+        type Currency: Currency<Self::AccountId>;
+
+        /// The fee paid by the creator of a task, to be shared by the verifiers of that task.
+        #[pallet::constant]
+        type TaskFee: Get<BalanceOf<Self>>;
+
+        /// The id of the reward pool, from which its account is derived.
+        #[pallet::constant]
+        type PalletId: Get<PalletId>;
+
+        /// Handler for the tasks which are settled as passed.
+        type OnTaskSettled: OnTaskSettled<Self::AccountId>;
+
+        /// Handler for the tasks which are settled as not passed.
+        type OnTaskRejected: OnTaskRejected<Self::AccountId>;
     
         /// After a task is verified, it can still be stored on chain for a `StorePeriod` of time
         #[pallet::constant]
@@ -189,18 +446,83 @@ pub mod pallet {
         /// multiple pallets send unsigned transactions.
         #[pallet::constant]
         type UnsignedPriority: Get<TransactionPriority>;
-    
+
+        /// The maximum number of tasks an account can have open at the same time.
+        #[pallet::constant]
+        type MaxOpenTasks: Get<u32>;
+
+        /// The maximum number of tasks of a class which can be open at the same time.
+        #[pallet::constant]
+        type MaxOpenClassTasks: Get<u32>;
+
+        /// The length of the windows in which the creation of tasks is rate limited.
+        #[pallet::constant]
+        type QuotaWindow: Get<Self::BlockNumber>;
+
+        /// The maximum number of tasks an account can create in a `QuotaWindow`.
+        #[pallet::constant]
+        type MaxTasksPerWindow: Get<u32>;
+
+        /// The maximum number of tasks of a class which can be created in a `QuotaWindow`.
+        #[pallet::constant]
+        type MaxClassTasksPerWindow: Get<u32>;
+
+        /// The maximum number of programs in a bundle.
+        #[pallet::constant]
+        type MaxBundleSize: Get<u32>;
+
+        /// The minimum security level in bits of the proofs, which a class can only raise.
+        #[pallet::constant]
+        type MinSecurityLevel: Get<u32>;
+
+        /// The size in bytes of the largest proof which can be verified by `verify_on_chain`. It must
+        /// not exceed the largest proof the call is benchmarked with.
+        #[pallet::constant]
+        type MaxOnChainProofSize: Get<u32>;
+
+        /// The size in bytes of the largest proof which can be given to `create_task`, to be indexed.
+        #[pallet::constant]
+        type MaxIndexedProofSize: Get<u32>;
+
+        /// Weight information for extrinsics in this pallet.
+        type WeightInfo: WeightInfo;
+
+        /// The signature of a `TaskAuthorization` by its subject.
+        type OffchainSignature: Verify<Signer = Self::OffchainPublic> + Parameter;
+
+        /// The key of the subject of a `TaskAuthorization`, identifying its account.
+        type OffchainPublic: IdentifyAccount<AccountId = Self::AccountId>;
     }
 
     #[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
 	pub struct Pallet<T>(PhantomData<T>);
 
+    #[pallet::storage]
+    #[pallet::getter(fn storage_version)]
+    /// The release of the storage layout, used to decide which migrations to apply
+    pub(super) type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
     #[pallet::storage]
     #[pallet::getter(fn keys)]
     /// Current set of keys that are allowed to execute verification tasks
     pub(super) type Keys<T: Config> = StorageValue<_, Vec<T::AuthorityId>, ValueQuery>;
 
+    #[pallet::storage]
+    #[pallet::getter(fn disabled_verifiers)]
+    /// Indexes into `Keys` of the verifiers disabled in the current session, sorted
+    pub(super) type DisabledVerifiers<T: Config> = StorageValue<_, Vec<u32>, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn historical_keys)]
+    /// The verifiers of the recent sessions with their keys, in the order of `auth_index`
+    pub(super) type HistoricalKeys<T: Config> = StorageMap<
+        _,
+        Twox64Concat, SessionIndex,
+        Vec<(T::AccountId, T::AuthorityId)>,
+        OptionQuery,
+    >;
+
     #[pallet::storage]
     #[pallet::getter(fn task_params)]
     /// Map from the task_params to the TaskInfo(proof_id,inputs,outputs)
@@ -220,7 +542,7 @@ pub mod pallet {
         _,
         Twox64Concat, T::AccountId,
         Twox64Concat, Class,
-        Status,
+        Status<T::AccountId>,
         OptionQuery,
     >;
 
@@ -234,9 +556,222 @@ pub mod pallet {
         bool,
         ValueQuery,
     >;
+
+    #[pallet::type_value]
+    pub(super) fn DefaultThreshold() -> Perbill { Perbill::from_percent(50) }
+
+    #[pallet::storage]
+    #[pallet::getter(fn vote_threshold)]
+    /// The share of the active verifiers whose votes settle a task
+    pub(super) type VoteThreshold<T: Config> = StorageValue<_, Perbill, ValueQuery, DefaultThreshold>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn classes)]
+    /// Registered classes, with their owner and the program proving them
+    pub(super) type Classes<T: Config> = StorageMap<
+        _,
+        Twox64Concat, Class,
+        ClassInfo<T::AccountId>,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn attestations)]
+    /// The attestations of the settled tasks, removed along with their `SettledTasks` entry
+    pub(super) type Attestations<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat, T::AccountId,
+        Twox64Concat, Class,
+        Attestation<T::BlockNumber>,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn class_validity)]
+    /// The number of blocks the attestations of a class are valid for, `StorePeriod` if not set
+    pub(super) type ClassValidity<T: Config> = StorageMap<
+        _,
+        Twox64Concat, Class,
+        T::BlockNumber,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn class_security_level)]
+    /// The minimum security level in bits of the proofs of a class, if it is above `MinSecurityLevel`
+    pub(super) type ClassSecurityLevel<T: Config> = StorageMap<
+        _,
+        Twox64Concat, Class,
+        u32,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn insecure_votes)]
+    /// The number of votes on an ongoing task whose proof is below the minimum security level
+    pub(super) type InsecureVotes<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat, T::AccountId,
+        Twox64Concat, Class,
+        u32,
+        ValueQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn revoked_attestations)]
+    /// The block number at which an attestation is revoked, removed along with the attestation
+    pub(super) type RevokedAttestations<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat, T::AccountId,
+        Twox64Concat, Class,
+        T::BlockNumber,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn task_fees)]
+    /// The fee of a task waiting in the reward pool until the task is settled
+    pub(super) type TaskFees<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat, T::AccountId,
+        Twox64Concat, Class,
+        BalanceOf<T>,
+        ValueQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn task_priority)]
+    /// The priority fee of an ongoing task and the block at which it is created,
+    /// deciding the order in which verifiers pick the tasks
+    pub(super) type TaskPriority<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat, T::AccountId,
+        Twox64Concat, Class,
+        (BalanceOf<T>, T::BlockNumber),
+        ValueQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn account_quota)]
+    /// The number of open tasks of an account, and of the tasks it created in the current window
+    pub(super) type AccountQuota<T: Config> = StorageMap<
+        _,
+        Twox64Concat, T::AccountId,
+        Quota<T::BlockNumber>,
+        ValueQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn class_quota)]
+    /// The number of open tasks of a class, and of its tasks created in the current window
+    pub(super) type ClassQuota<T: Config> = StorageMap<
+        _,
+        Twox64Concat, Class,
+        Quota<T::BlockNumber>,
+        ValueQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn quota_exempt)]
+    /// Trusted accounts which are not limited by the quotas
+    pub(super) type QuotaExempt<T: Config> = StorageMap<
+        _,
+        Twox64Concat, T::AccountId,
+        bool,
+        ValueQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn task_bundles)]
+    /// The components of the tasks which are bundles, in the order their outputs are reported
+    pub(super) type TaskBundles<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat, T::AccountId,
+        Twox64Concat, Class,
+        Vec<TaskInfo>,
+        ValueQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn authorization_nonces)]
+    /// The nonce the next `TaskAuthorization` of an account has to carry
+    pub(super) type AuthorizationNonces<T: Config> = StorageMap<
+        _,
+        Twox64Concat, T::AccountId,
+        u64,
+        ValueQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn task_voters)]
+    /// The accounts of the verifiers who have voted on an ongoing task, with their votes
+    pub(super) type TaskVoters<T: Config> = StorageDoubleMap<
+        _,
+        Twox64Concat, T::AccountId,
+        Twox64Concat, Class,
+        Vec<(T::AccountId, bool)>,
+        ValueQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn pending_rewards)]
+    /// Rewards credited to verifiers which have not been claimed yet
+    pub(super) type PendingRewards<T: Config> = StorageMap<
+        _,
+        Twox64Concat, T::AccountId,
+        BalanceOf<T>,
+        ValueQuery,
+    >;
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        /// The verifiers of the genesis session with their keys.
+        /// Leave it empty if the keys are provided by the session module.
+        pub keys: Vec<(T::AccountId, T::AuthorityId)>,
+        /// Classes to register, with their owner and the hash of their program.
+        pub classes: Vec<(Class, T::AccountId, [u8; 32])>,
+        /// The share of the active verifiers whose votes settle a task.
+        pub threshold: Perbill,
+        /// Tasks which are already settled, with whether they are passed or not.
+        pub settled_tasks: Vec<(T::AccountId, Class, TaskInfo, bool)>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            Self {
+                keys: Vec::new(),
+                classes: Vec::new(),
+                threshold: DefaultThreshold::get(),
+                settled_tasks: Vec::new(),
+            }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        fn build(&self) {
+            StorageVersion::<T>::put(Releases::LATEST);
+            if !self.keys.is_empty() {
+                let keys = self.keys.iter().map(|x| x.1.clone()).collect::<Vec<_>>();
+                Pallet::<T>::initialize_keys(&keys);
+                Pallet::<T>::note_session_keys(T::ValidatorSet::session_index(), self.keys.clone());
+            }
+            VoteThreshold::<T>::put(self.threshold);
+            for (class, owner, program_hash) in &self.classes {
+                Classes::<T>::insert(class, ClassInfo { owner: owner.clone(), program_hash: *program_hash });
+            }
+            let expiration = <frame_system::Pallet<T>>::block_number().saturating_add(T::StorePeriod::get());
+            for (who, class, task, passed) in &self.settled_tasks {
+                TaskParams::<T>::insert(who, class, task);
+                Pallet::<T>::record_settlement(who, class, task.program_hash, *passed, expiration);
+            }
+        }
+    }
+
     #[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
-	#[pallet::metadata(T::AccountId = "AccountId")]
+	#[pallet::metadata(T::AccountId = "AccountId", BalanceOf<T> = "Balance")]
     pub enum Event<T: Config> {
         /// A new verifier is added with `AccountId`.
         AddVerifier(T::AccountId),
@@ -244,6 +779,28 @@ pub mod pallet {
         RemoveVerifier(T::AccountId),
         /// A new task is created.
         TaskCreated([u8; 32]),
+        /// A reward is credited to a verifier. \[verifier, amount\]
+        RewardCredited(T::AccountId, BalanceOf<T>),
+        /// A verifier has claimed its rewards. \[verifier, amount\]
+        RewardClaimed(T::AccountId, BalanceOf<T>),
+        /// An account is exempted from the quotas or not anymore. \[account, exempt\]
+        QuotaExemptSet(T::AccountId, bool),
+        /// The validity period of the attestations of a class is set. \[class, validity\]
+        ClassValiditySet(Class, Option<T::BlockNumber>),
+        /// An attestation is revoked. \[subject, class, revoker\]
+        AttestationRevoked(T::AccountId, Class, T::AccountId),
+        /// An attestation has expired and is removed. \[subject, class\]
+        AttestationExpired(T::AccountId, Class),
+        /// A task is created on behalf of its subject. \[subject, relayer\]
+        TaskCreatedFor(T::AccountId, T::AccountId),
+        /// The minimum security level of the proofs of a class is set. \[class, level\]
+        ClassSecurityLevelSet(Class, Option<u32>),
+        /// A task is rejected because its proof is below the minimum security level. \[subject, class\]
+        TaskRejectedInsecure(T::AccountId, Class),
+        /// A task is verified and settled on chain. \[subject, class, passed\]
+        TaskVerifiedOnChain(T::AccountId, Class, bool),
+        /// A class is registered. \[class, owner\]
+        ClassRegistered(Class, T::AccountId),
     }
 
     #[pallet::error]
@@ -256,6 +813,44 @@ pub mod pallet {
 		TaskNotExists,
 		/// Duplicated Submission
 		DuplicatedSubmission,
+		/// There is no reward to claim
+		NoRewards,
+		/// The verifier is disabled in the current session
+		VerifierDisabled,
+		/// The program of the task is not the one registered for its class
+		ProgramMismatch,
+		/// The account has too many open tasks, or has created too many in the current window
+		AccountQuotaExceeded,
+		/// The class has too many open tasks, or too many are created in the current window
+		ClassQuotaExceeded,
+		/// The class is not registered
+		ClassNotExists,
+		/// Only the owner of the class can change it
+		NotClassOwner,
+		/// There is no attestation for the account and the class
+		AttestationNotExists,
+		/// The attestation is already revoked
+		AttestationRevoked,
+		/// Only the owner of the class or the creator of the task can revoke an attestation
+		RevocationNotAllowed,
+		/// The authorization is not signed by its subject
+		BadAuthorization,
+		/// The authorization is used after the block it is valid until
+		AuthorizationExpired,
+		/// The nonce of the authorization is not the next one of its subject
+		InvalidNonce,
+		/// A bundle has no component
+		EmptyBundle,
+		/// A bundle has more components than `MaxBundleSize`
+		BundleTooLarge,
+		/// The proof is larger than `MaxOnChainProofSize`, or than `MaxIndexedProofSize` if it is to be indexed
+		ProofTooLarge,
+		/// The runtime can not verify proofs of this backend
+		VerificationUnavailable,
+		/// The class is already registered
+		ClassAlreadyExists,
+		/// The proofs of this backend can not be verified by every verifier, so the task would never be settled
+		UnsupportedBackend,
     }
 
     #[pallet::call]
@@ -266,26 +861,158 @@ pub mod pallet {
         /// The dispatch origin for this call must be _Signed_.
         /// 
 		/// - `program_hash`: The hash of task to be verified.
+		/// - `backend`: The proof system the program is proved with.
 		/// - `inputs`: Inputs of the task.
         /// - `outputs`: Outputs of the task.
         /// - `proof_id`: The id of the proof,combined with a url to fetch the complete proof later
+        /// - `proof`: The proof itself, optionally, no larger than `MaxIndexedProofSize`. It is written
+        ///   to the offchain DB of the nodes run with `--enable-offchain-indexing`, so that their
+        ///   workers do not have to fetch it. It is not kept in state.
+        /// - `priority_fee`: An optional fee on top of the `TaskFee`, to be verified earlier.
         /// 
-        /// If the Task created successfully, deposit the `TaskCreated` event.
-        #[pallet::weight(10000)]
+        /// The `TaskFee` and the priority fee are transferred from the creator into the reward pool.
+        /// If the Task created successfully, deposit the `TaskCreated` event. The weight of the call
+        /// grows with the size of the proof to index.
+        #[pallet::weight(T::WeightInfo::create_task(proof.as_ref().map_or(0, |proof| proof.len() as u32)))]
         pub fn create_task(
             origin: OriginFor<T>,
             class: Class,
             program_hash: [u8; 32],
+            backend: Backend,
             inputs: Vec<u128>,
             outputs: Vec<u128>,
-            proof_id: Vec<u8>
+            proof_id: Vec<u8>,
+            proof: Option<Vec<u8>>,
+            priority_fee: Option<BalanceOf<T>>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            if let Some(proof) = &proof {
+                ensure!(proof.len() as u32 <= T::MaxIndexedProofSize::get(), Error::<T>::ProofTooLarge);
+            }
+            let info = TaskInfo{proof_id: proof_id.clone(), inputs, outputs, program_hash, backend};
+            Self::do_create_task(&who, &who, class.clone(), info, priority_fee)?;
+            if let Some(proof) = proof {
+                // The id is indexed along with the proof, for the workers to tell a stale proof
+                // of a previous task of the same subject and class.
+                sp_io::offchain_index::set(&Self::proof_index_key(&who, &class), &(proof_id, proof).encode());
+            }
+            Ok(())
+        }
+
+        /// Create a bundle of tasks, which is settled as passed only if all of its components pass.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// - `components`: The programs to be proved, with their inputs, outputs and proofs.
+        /// - `priority_fee`: An optional fee on top of the `TaskFee`, to be verified earlier.
+        ///
+        /// The bundle pays a single `TaskFee`, and its program hash is the `bundle_hash` of its
+        /// components. If the bundle created successfully, deposit the `TaskCreated` event.
+        #[pallet::weight(10000)]
+        pub fn create_bundle(
+            origin: OriginFor<T>,
+            class: Class,
+            components: Vec<TaskInfo>,
+            priority_fee: Option<BalanceOf<T>>,
         ) -> DispatchResult {
             let who = ensure_signed(origin)?;
+            ensure!(!components.is_empty(), Error::<T>::EmptyBundle);
+            ensure!(components.len() as u32 <= T::MaxBundleSize::get(), Error::<T>::BundleTooLarge);
+            ensure!(
+                components.iter().all(|component| Self::is_supported_backend(component.backend)),
+                Error::<T>::UnsupportedBackend
+            );
+            let info = TaskInfo {
+                proof_id: Vec::new(),
+                inputs: Vec::new(),
+                outputs: Vec::new(),
+                program_hash: Self::bundle_hash(&components),
+                backend: Backend::default(),
+            };
+            Self::do_create_task(&who, &who, class.clone(), info, priority_fee)?;
+            <TaskBundles<T>>::insert(&who, &class, components);
+            Ok(())
+        }
+
+        /// Verify `proof` in the runtime and settle the task at once, as passed or not.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// - `proof`: The proof itself, no larger than `MaxOnChainProofSize`.
+        ///
+        /// The weight of the call grows with the size of the proof. If the proof can not be judged
+        /// by the runtime, e.g. because of its backend, no task is created.
+        #[pallet::weight(T::WeightInfo::verify_on_chain(proof.len() as u32))]
+        pub fn verify_on_chain(
+            origin: OriginFor<T>,
+            class: Class,
+            program_hash: [u8; 32],
+            backend: Backend,
+            inputs: Vec<u128>,
+            outputs: Vec<u128>,
+            proof: Vec<u8>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(proof.len() as u32 <= T::MaxOnChainProofSize::get(), Error::<T>::ProofTooLarge);
             // Ensure task has not been created
-            ensure!(!TaskParams::<T>::try_get(&who, &class).is_ok(), Error::<T>::TaskAlreadyExists);
-            <TaskParams<T>>::insert(&who, &class, TaskInfo{proof_id, inputs, outputs, program_hash: program_hash});
-            <OngoingTasks<T>>::insert(&who, &class, Status::default());
-            Self::deposit_event(Event::TaskCreated(program_hash));
+            ensure!(Self::can_create_task(&who, &class), Error::<T>::TaskAlreadyExists);
+            // A registered class can only be proved by its own program
+            if let Some(info) = Self::classes(&class) {
+                ensure!(info.program_hash == program_hash, Error::<T>::ProgramMismatch);
+            }
+            // Only the backends judged the same way by every node can settle a task in a block
+            ensure!(Self::is_supported_backend(backend), Error::<T>::UnsupportedBackend);
+            let verdict = Self::judge_proof(
+                backend, &program_hash, inputs.clone(), outputs.clone(), &proof, Self::min_security_level(&class),
+            ).map_err(|_| Error::<T>::VerificationUnavailable)?;
+
+            Self::note_new_task(&who, &who, &class)?;
+            Self::clear_revoked_task(&who, &class);
+            <TaskParams<T>>::insert(&who, &class, TaskInfo{proof_id: Vec::new(), inputs, outputs, program_hash, backend});
+            let now = <frame_system::Pallet<T>>::block_number();
+            let expiration = now.saturating_add(Self::class_validity(&class).unwrap_or_else(T::StorePeriod::get));
+            let passed = verdict == Verdict::Passed;
+            Self::settle_task(who.clone(), class.clone(), passed, expiration);
+            if verdict == Verdict::Insecure {
+                Self::deposit_event(Event::TaskRejectedInsecure(who.clone(), class.clone()));
+            }
+            Self::deposit_event(Event::TaskVerifiedOnChain(who, class, passed));
+            Ok(())
+        }
+
+        /// Create a task on behalf of `subject`, which has signed `authorization` off-chain.
+        ///
+        /// The dispatch origin for this call must be _Signed_ by the relayer, which pays the
+        /// `TaskFee` and the priority fee. The task and its attestation belong to `subject`.
+        ///
+        /// - `authorization`: The task, with the next nonce of `subject` and the block number
+        ///   it is valid until.
+        /// - `signature`: The signature of the `authorization_payload` of `authorization` by `subject`.
+        ///
+        /// If the Task created successfully, deposit the `TaskCreated` and `TaskCreatedFor` events.
+        #[pallet::weight(10000)]
+        pub fn create_task_for(
+            origin: OriginFor<T>,
+            subject: T::AccountId,
+            authorization: TaskAuthorization<T::BlockNumber>,
+            signature: T::OffchainSignature,
+            priority_fee: Option<BalanceOf<T>>,
+        ) -> DispatchResult {
+            let relayer = ensure_signed(origin)?;
+            ensure!(
+                signature.verify(&Self::authorization_payload(&authorization)[..], &subject),
+                Error::<T>::BadAuthorization
+            );
+            ensure!(
+                <frame_system::Pallet<T>>::block_number() <= authorization.valid_until,
+                Error::<T>::AuthorizationExpired
+            );
+            ensure!(authorization.nonce == Self::authorization_nonces(&subject), Error::<T>::InvalidNonce);
+
+            let TaskAuthorization { class, program_hash, backend, inputs, outputs, proof_id, .. } = authorization;
+            Self::do_create_task(&relayer, &subject, class, TaskInfo{proof_id, inputs, outputs, program_hash, backend}, priority_fee)?;
+            <AuthorizationNonces<T>>::mutate(&subject, |nonce| *nonce += 1);
+            Self::deposit_event(Event::TaskCreatedFor(subject, relayer));
             Ok(())
         }
 
@@ -312,31 +1039,51 @@ pub mod pallet {
                 |last_status| -> DispatchResult {
                     // Last status must exist.Fetch last status,if not exists return error
                     let mut status = last_status.take().ok_or(Error::<T>::TaskNotExists)?;
-                    // A verifier can not submit more than once
-                    ensure!(!status.verifiers.contains(&receipt.auth_index),
+                    // Resolve the verifier in the key set of the session the receipt is signed in
+                    let (verifier, authority_id) = Self::historical_keys(receipt.session_index)
+                        .and_then(|keys| keys.get(receipt.auth_index as usize).cloned())
+                        .ok_or(Error::<T>::NotAllowed)?;
+                    // A disabled verifier can not vote
+                    ensure!(!Self::is_disabled(&authority_id), Error::<T>::VerifierDisabled);
+                    // A verifier can not submit more than once, even with the key of another session
+                    ensure!(!status.verifiers.contains(&verifier),
                         Error::<T>::DuplicatedSubmission);
                     // Update the verifier list
-                    status.verifiers.push(receipt.auth_index);
-                    // > 50%
-                    let threshold = (Self::authority_len() + 1) / 2;
+                    status.verifiers.push(verifier.clone());
+                    // A proof below the minimum security level does not pass, whether it holds or not
+                    let passed = receipt.passed && !receipt.insecure;
+                    if receipt.insecure {
+                        <InsecureVotes<T>>::mutate(&account, &class, |votes| *votes += 1);
+                    }
+                    // Remember the account behind the vote, to reward it once the task is settled
+                    <TaskVoters<T>>::append(&account, &class, (verifier, passed));
+                    // The share of the verifiers which are not disabled, 50% by default
+                    let threshold = Self::vote_threshold().mul_ceil(Self::active_authority_len()).max(1);
                     // Adjust ayes or nays according to the receipt.
-                    if receipt.passed {
+                    if passed {
                         status.ayes += 1;
                     } else {
                         status.nays += 1;
                     }
-                    // Change expiration.
-                    let expiration = receipt.submit_at + T::StorePeriod::get();
+                    // Change expiration, according to the validity period of the class if it is set.
+                    let expiration = Self::class_validity(&class).map_or(
+                        receipt.submit_at.saturating_add(T::StorePeriod::get()),
+                        |validity| <frame_system::Pallet<T>>::block_number().saturating_add(validity),
+                    );
                     // If ayes >= threshold，pass the task and store it on-chain with a `true`.
                     if status.ayes >= threshold {
                         // Pass the verification
-                        SettledTasks::<T>::insert(expiration, &(account, class), true);
+                        Self::settle_task(account, class, true, expiration);
                         *last_status = None;
                     
                     // If nays >= threshold，reject the task and store it on-chain with a `false`.
                     } else if status.nays >= threshold {
-                        // fail the verification
-                        SettledTasks::<T>::insert(expiration, &(account, class), false);
+                        // fail the verification, telling apart the proofs which are not secure enough
+                        let insecure = Self::insecure_votes(&account, &class) >= threshold;
+                        Self::settle_task(account.clone(), class.clone(), false, expiration);
+                        if insecure {
+                            Self::deposit_event(Event::TaskRejectedInsecure(account, class));
+                        }
                         *last_status = None;
                     } else {
                         // Otherwise, update the task status
@@ -345,13 +1092,134 @@ pub mod pallet {
                     Ok(())
             })
         }
+
+        /// Claim all the rewards credited to the caller.
+        ///
+        /// The dispatch origin for this call must be _Signed_.
+        ///
+        /// The rewards are transferred from the reward pool and the `RewardClaimed` event is deposited.
+        #[pallet::weight(10000)]
+        pub fn claim_rewards(origin: OriginFor<T>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let reward = <PendingRewards<T>>::get(&who);
+            ensure!(!reward.is_zero(), Error::<T>::NoRewards);
+            T::Currency::transfer(&Self::account_id(), &who, reward, ExistenceRequirement::AllowDeath)?;
+            <PendingRewards<T>>::remove(&who);
+            Self::deposit_event(Event::RewardClaimed(who, reward));
+            Ok(())
+        }
+
+        /// Exempt an account from the quotas on task creation, or revoke its exemption.
+        ///
+        /// The dispatch origin for this call must be _Root_.
+        #[pallet::weight(10000)]
+        pub fn set_quota_exempt(origin: OriginFor<T>, who: T::AccountId, exempt: bool) -> DispatchResult {
+            ensure_root(origin)?;
+            if exempt {
+                <QuotaExempt<T>>::insert(&who, true);
+            } else {
+                <QuotaExempt<T>>::remove(&who);
+            }
+            Self::deposit_event(Event::QuotaExemptSet(who, exempt));
+            Ok(())
+        }
+
+        /// Register `class`, so that its tasks can only be proved by `program_hash`.
+        ///
+        /// The dispatch origin for this call must be _Signed_, and becomes the owner of the class.
+        #[pallet::weight(10000)]
+        pub fn register_class(origin: OriginFor<T>, class: Class, program_hash: [u8; 32]) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(!Classes::<T>::contains_key(&class), Error::<T>::ClassAlreadyExists);
+            <Classes<T>>::insert(&class, ClassInfo { owner: who.clone(), program_hash });
+            Self::deposit_event(Event::ClassRegistered(class, who));
+            Ok(())
+        }
+
+        /// Set the number of blocks the attestations of a class are valid for, or fall back
+        /// to `StorePeriod` with `None`. Only the attestations settled afterwards are affected.
+        ///
+        /// The dispatch origin for this call must be _Signed_ by the owner of the class.
+        #[pallet::weight(10000)]
+        pub fn set_class_validity(
+            origin: OriginFor<T>,
+            class: Class,
+            validity: Option<T::BlockNumber>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let info = Self::classes(&class).ok_or(Error::<T>::ClassNotExists)?;
+            ensure!(info.owner == who, Error::<T>::NotClassOwner);
+            <ClassValidity<T>>::set(&class, validity);
+            Self::deposit_event(Event::ClassValiditySet(class, validity));
+            Ok(())
+        }
+
+        /// Set the minimum security level in bits of the proofs of a class, or fall back to
+        /// `MinSecurityLevel` with `None`. A level below `MinSecurityLevel` has no effect.
+        ///
+        /// The dispatch origin for this call must be _Signed_ by the owner of the class.
+        #[pallet::weight(10000)]
+        pub fn set_class_security_level(
+            origin: OriginFor<T>,
+            class: Class,
+            level: Option<u32>,
+        ) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            let info = Self::classes(&class).ok_or(Error::<T>::ClassNotExists)?;
+            ensure!(info.owner == who, Error::<T>::NotClassOwner);
+            <ClassSecurityLevel<T>>::set(&class, level);
+            Self::deposit_event(Event::ClassSecurityLevelSet(class, level));
+            Ok(())
+        }
+
+        /// Revoke the attestation of `who` for `class` before it expires.
+        ///
+        /// The dispatch origin for this call must be _Signed_ by the owner of the class,
+        /// or by `who` itself.
+        ///
+        /// The attestation is kept until it expires, marked in `RevokedAttestations`, or until
+        /// its subject creates a new task for the class.
+        #[pallet::weight(10000)]
+        pub fn revoke_attestation(origin: OriginFor<T>, who: T::AccountId, class: Class) -> DispatchResult {
+            let revoker = ensure_signed(origin)?;
+            ensure!(Attestations::<T>::contains_key(&who, &class), Error::<T>::AttestationNotExists);
+            ensure!(!RevokedAttestations::<T>::contains_key(&who, &class), Error::<T>::AttestationRevoked);
+            ensure!(
+                revoker == who || Self::classes(&class).map_or(false, |info| info.owner == revoker),
+                Error::<T>::RevocationNotAllowed
+            );
+            <RevokedAttestations<T>>::insert(&who, &class, <frame_system::Pallet<T>>::block_number());
+            Self::deposit_event(Event::AttestationRevoked(who, class, revoker));
+            Ok(())
+        }
     }
 
     // Runs after every block.  
     #[pallet::hooks]
     impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_runtime_upgrade() -> Weight {
+            migrations::migrate::<T>()
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<(), &'static str> {
+            migrations::pre_migrate::<T>()
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade() -> Result<(), &'static str> {
+            migrations::post_migrate::<T>()
+        }
+
         fn on_finalize(block: T::BlockNumber) {
-            SettledTasks::<T>::remove_prefix(block);
+            for ((account, class), _) in SettledTasks::<T>::drain_prefix(block) {
+                // The subject can create a task for the class again
+                TaskParams::<T>::remove(&account, &class);
+                TaskBundles::<T>::remove(&account, &class);
+                Attestations::<T>::remove(&account, &class);
+                RevokedAttestations::<T>::remove(&account, &class);
+                Self::deposit_event(Event::AttestationExpired(account, class));
+            }
         }
 
         fn offchain_worker(now: T::BlockNumber) {
@@ -382,8 +1250,9 @@ pub mod pallet {
         }
     }
 
-    /// Invalid transaction custom error. Returned when validators_len field in Receipt is incorrect.
-    const INVALID_VALIDATORS_LEN: u8 = 10;
+    /// Invalid transaction custom error. Returned when the receipt is signed by a disabled verifier.
+    const DISABLED_VERIFIER: u8 = 10;
+
   #[pallet::validate_unsigned]
     impl<T: Config> frame_support::unsigned::ValidateUnsigned for Pallet<T> {
         type Call = Call<T>;
@@ -398,17 +1267,26 @@ pub mod pallet {
                     return InvalidTransaction::Future.into();
                 }
 
-                // Verify that the incoming (unverified) pubkey is actually an authority id
-                let keys = Keys::<T>::get();
-                if keys.len() as u32 != receipt.validators_len {
-                    return InvalidTransaction::Custom(INVALID_VALIDATORS_LEN).into();
+                if receipt.session_index > T::ValidatorSet::session_index() {
+                    return InvalidTransaction::Future.into();
                 }
-           
+
+                // Verify that the incoming (unverified) pubkey is actually an authority id
+                // of the session in which the receipt is signed.
+                let keys = match HistoricalKeys::<T>::get(receipt.session_index) {
+                    Some(keys) => keys,
+                    None => return InvalidTransaction::Stale.into(),
+                };
+
                 let authority_id = match keys.get(receipt.auth_index as usize) {
-                    Some(id) => id,
+                    Some((_, id)) => id,
                     None => return InvalidTransaction::BadProof.into(),
                 };
 
+                if Pallet::<T>::is_disabled(authority_id) {
+                    return InvalidTransaction::Custom(DISABLED_VERIFIER).into();
+                }
+
                 // Check signature (this is expensive so we do it last).
                 let signature_valid = receipt.using_encoded(|encoded_receipt| {
                     authority_id.verify(&encoded_receipt, &signature)
@@ -481,7 +1359,10 @@ impl<T: Config> Pallet<T> {
             }
         })?;
 
-        let mut local_tasks = res.map_err(|_| OffchainErr::FailToAcquireLock)?;
+        let mut local_tasks = res.map_err(|_| {
+            sp_starks::starks::report(WorkerEvent::LockContended);
+            OffchainErr::FailToAcquireLock
+        })?;
 
         // We got the lock, and do the fetch, verify, sign and send
         let res =  Self::prepare_submission(
@@ -506,25 +1387,33 @@ impl<T: Config> Pallet<T> {
         block_number: T::BlockNumber,
         task_tuple_id: (T::AccountId, Class)
     ) -> OffchainResult<T, ()> {
-        let TaskInfo {proof_id, inputs, outputs, program_hash } = Self::task_params(&task_tuple_id.0, &task_tuple_id.1);
-        
-        log::info!("$$$$$$$ FETCHING");
-        // To fetch proof and verify it.
-        let proof = Self::fetch_proof(&proof_id).map_err(|_| OffchainErr::FailedToFetchProof)?;
-        let is_success: bool = Self::stark_verify(&program_hash, inputs,outputs, &proof)?;
+        let info = Self::task_params(&task_tuple_id.0, &task_tuple_id.1);
+        let program_hash = info.program_hash;
+        let bundle = Self::task_bundles(&task_tuple_id.0, &task_tuple_id.1);
+
+        let min_security_level = Self::min_security_level(&task_tuple_id.1);
+
+        // A bundle passes only if all of its components do, so stop at the first failure.
+        let mut verdict = Verdict::Passed;
+        for component in if bundle.is_empty() { vec![info] } else { bundle } {
+            verdict = Self::verify_task(&task_tuple_id, component, min_security_level)?;
+            if verdict != Verdict::Passed {
+                break;
+            }
+        }
         
-        log::debug!("$$$$$$$ SUCCESS or NOT :{:?}", is_success);
+        log::debug!("$$$$$$$ SUCCESS or NOT :{:?}", verdict);
         
 
-        let validators_len = Keys::<T>::decode_len().unwrap_or_default() as u32;
         //Create and initialize a verification receipt
         let receipt = VerificationReceipt {
             task_tuple_id,
             program_hash: program_hash,
-            passed: is_success,
+            passed: verdict == Verdict::Passed,
+            insecure: verdict == Verdict::Insecure,
             submit_at: block_number,
             auth_index: auth_index,
-            validators_len
+            session_index: T::ValidatorSet::session_index(),
         };
 
         let signature = key.sign(&receipt.encode()).ok_or(OffchainErr::FailedSigning)?;
@@ -539,14 +1428,127 @@ impl<T: Config> Pallet<T> {
             block_number
         );
 
-        SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into())
-            .map_err(|_| OffchainErr::SubmitTransaction(block_number))?;
+        let submitted = SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into());
+        sp_starks::starks::report(WorkerEvent::Submitted { success: submitted.is_ok() });
+        submitted.map_err(|_| OffchainErr::SubmitTransaction(block_number))?;
         
         Ok(())
     }
 
-    /// Fetch the proof     
-    fn fetch_proof(proof_id: &Vec<u8>) -> Result<Vec<u8>, http::Error> {
+    /// Fetch the proof of a task and verify it, unless it is below `min_security_level`
+    fn verify_task(
+        task_tuple_id: &(T::AccountId, Class),
+        info: TaskInfo,
+        min_security_level: u32,
+    ) -> OffchainResult<T, Verdict> {
+        let TaskInfo {proof_id, inputs, outputs, program_hash, backend } = info;
+        log::info!("$$$$$$$ FETCHING");
+        // To fetch proof and verify it.
+        let started = sp_io::offchain::timestamp();
+        let proof = Self::fetch_proof(task_tuple_id, &proof_id);
+        sp_starks::starks::report(WorkerEvent::ProofFetched {
+            millis: sp_io::offchain::timestamp().diff(&started).millis(),
+            success: proof.is_ok(),
+        });
+        let proof = proof.map_err(|_| OffchainErr::FailedToFetchProof)?;
+        let key = (backend, program_hash, &inputs, &outputs, sp_io::hashing::blake2_256(&proof), min_security_level)
+            .using_encoded(sp_io::hashing::blake2_256);
+        if let Some(verdict) = Self::cached_verdict(&key) {
+            return Ok(verdict);
+        }
+        let verdict = Self::judge_proof(backend, &program_hash, inputs, outputs, &proof, min_security_level)
+            .map_err(OffchainErr::VerificationFailed)?;
+        Self::cache_verdict(key, verdict);
+        Ok(verdict)
+    }
+
+    /// Look `key` up in the `VerificationCache`, and log its hit rate.
+    fn cached_verdict(key: &[u8; 32]) -> Option<Verdict> {
+        let mut verdict = None;
+        let mut hit_rate = (0, 0);
+        // Losing a race with another worker only loses the bookkeeping of this lookup.
+        let _ = StorageValueRef::persistent(CACHE_DB_KEY).mutate(
+            |cache: Option<Option<VerificationCache>>| -> Result<_, ()> {
+                let mut cache = cache.flatten().unwrap_or_default();
+                verdict = cache.lookup(key);
+                hit_rate = (cache.hits, cache.hits.saturating_add(cache.misses));
+                Ok(cache)
+            });
+        log::debug!(
+            target: "starks-verifier",
+            "Verification cache {} for {:?}, {} hits out of {} lookups",
+            if verdict.is_some() { "hit" } else { "miss" },
+            key,
+            hit_rate.0,
+            hit_rate.1,
+        );
+        verdict
+    }
+
+    /// Remember the verdict for `key` in the `VerificationCache`.
+    fn cache_verdict(key: [u8; 32], verdict: Verdict) {
+        let _ = StorageValueRef::persistent(CACHE_DB_KEY).mutate(
+            |cache: Option<Option<VerificationCache>>| -> Result<_, ()> {
+                let mut cache = cache.flatten().unwrap_or_default();
+                cache.insert(key, verdict);
+                Ok(cache)
+            });
+    }
+
+    /// Check that `proof` is secure enough, then verify it. This is deterministic, so that it
+    /// can be run on chain as well as by the offchain workers.
+    fn judge_proof(
+        backend: Backend,
+        program_hash: &[u8; 32],
+        inputs: Vec<u128>,
+        outputs: Vec<u128>,
+        proof: &[u8],
+        min_security_level: u32,
+    ) -> Result<Verdict, VerifyError> {
+        if !Self::is_secure_enough(backend, proof, min_security_level)? {
+            return Ok(Verdict::Insecure);
+        }
+        Ok(if Self::stark_verify(backend, program_hash, inputs, outputs, proof)? {
+            Verdict::Passed
+        } else {
+            Verdict::Failed
+        })
+    }
+
+    /// Whether the security level claimed by `proof` is at least `min_security_level`
+    fn is_secure_enough(backend: Backend, proof: &[u8], min_security_level: u32) -> Result<bool, VerifyError> {
+        match sp_starks::starks::security_level(backend, proof) {
+            Ok(level) => Ok(level >= min_security_level),
+            // A proof which can not be decoded is rejected by `stark_verify` anyway.
+            Err(VerifyError::MalformedProof) | Err(VerifyError::InvalidProof) => Ok(true),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// The key the proof given to `create_task` by `account` for `class` is indexed under.
+    pub fn proof_index_key(account: &T::AccountId, class: &Class) -> Vec<u8> {
+        (PROOF_INDEX_PREFIX, account, class).encode()
+    }
+
+    /// The proof of `proof_id` indexed by `create_task` for the task `task_tuple_id`, if the node
+    /// indexes them.
+    fn indexed_proof(task_tuple_id: &(T::AccountId, Class), proof_id: &Vec<u8>) -> Option<Vec<u8>> {
+        let key = Self::proof_index_key(&task_tuple_id.0, &task_tuple_id.1);
+        match StorageValueRef::persistent(&key).get::<(Vec<u8>, Vec<u8>)>() {
+            Some(Some((indexed_id, proof))) if &indexed_id == proof_id => Some(proof),
+            _ => None,
+        }
+    }
+
+    /// Fetch the proof, from the offchain DB if it is indexed there, from the local proof store
+    /// of the node if it has it, or from IPFS.
+    fn fetch_proof(task_tuple_id: &(T::AccountId, Class), proof_id: &Vec<u8>) -> Result<Vec<u8>, http::Error> {
+        if let Some(proof) = Self::indexed_proof(task_tuple_id, proof_id) {
+            return Ok(proof);
+        }
+        if let Some(proof) = sp_starks::starks::fetch_local_proof(proof_id) {
+            return Ok(proof);
+        }
         let deadline = sp_io::offchain::timestamp().add(Duration::from_millis(100_000));
         // Combine the the `proof_id` with a certain url 
         let url = "https://ipfs.infura.io:5001/api/v0/cat?arg=".to_owned() + sp_std::str::from_utf8(proof_id).unwrap();
@@ -577,25 +1579,33 @@ impl<T: Config> Pallet<T> {
 
     /// Use Stark_verify to verify every program_hash with proof
     fn stark_verify(
+        backend: Backend,
         program_hash: &[u8; 32], 
         inputs: Vec<u128>,
         outputs: Vec<u128>,
-        proof: &[u8]) -> OffchainResult<T, bool> {
-        //To verify program hash，inputs，outputs，proof. 
-        sp_starks::starks::verify(program_hash, &inputs, &outputs, proof)
-            .map_err(|_| OffchainErr::VerificationFailed)
+        proof: &[u8]) -> Result<bool, VerifyError> {
+        //To verify program hash，inputs，outputs，proof with the verifier of its backend.
+        match sp_starks::starks::verify(backend, program_hash, &inputs, &outputs, proof) {
+            Ok(passed) => Ok(passed),
+            // The proof is at fault, so the task is rejected.
+            Err(VerifyError::MalformedProof) | Err(VerifyError::InvalidProof) => Ok(false),
+            // This node can not tell, so leave the task to the others.
+            Err(error) => Err(error),
+        }
     }
 
     // Return index of on-chain authorities and its corresponding local public key
     fn local_authority_keys() -> impl Iterator<Item=(u32, T::AuthorityId)> {
         // On-chain storage
         let authorities = Keys::<T>::get();
+        let disabled = DisabledVerifiers::<T>::get();
         // Local keystore
         let mut local_keys = T::AuthorityId::all();
 
         local_keys.sort();
         authorities.into_iter()
             .enumerate()
+            .filter(move |(index, _)| disabled.binary_search(&(*index as u32)).is_err())
             .filter_map(move |(index, authority)| {
                 local_keys.binary_search(&authority)
                     .ok()
@@ -604,22 +1614,200 @@ impl<T: Config> Pallet<T> {
     }
 
 
-    /// Pick an on-chain tasks to execute which is not included in `local_tasks`
+    /// Pick an on-chain tasks to execute which is not included in `local_tasks`.
+    /// The task with the highest priority fee is picked first, then the oldest one.
     fn task_to_execute(local_tasks: &BTreeSet<(T::AccountId, Class)>) -> OffchainResult<T, (T::AccountId, Class)> {
         //On-chain ready-to-verify tasks,put all task_hash of OngoingTasks into a vec.
         let ongoing_tasks_list = BTreeSet::from_iter(OngoingTasks::<T>::iter()
         .map(|(account_id, class, _)| (account_id, class)));
 
-        // Find any one task that is not executed
-        // TODO： find all tasks that are not executed
-        let mut tasks_not_executed : Vec<_> = ongoing_tasks_list.difference(local_tasks).cloned().collect();
-        
-        // return the task to execute
-        tasks_not_executed.pop().ok_or(OffchainErr::NoTaskToExecute)
+        // Find the most urgent task that is not executed
+        ongoing_tasks_list.difference(local_tasks)
+            .max_by_key(|(account_id, class)| {
+                let (priority_fee, created_at) = Self::task_priority(account_id, class);
+                (priority_fee, Reverse(created_at))
+            })
+            .cloned()
+            .ok_or(OffchainErr::NoTaskToExecute)
+    }
+
+    /// The minimum security level in bits of the proofs of `class`
+    pub fn min_security_level(class: &Class) -> u32 {
+        Self::class_security_level(class).map_or(T::MinSecurityLevel::get(), |level| level.max(T::MinSecurityLevel::get()))
+    }
+
+    /// The program hash of a bundle, binding the programs of its components in their order.
+    pub fn bundle_hash(components: &[TaskInfo]) -> [u8; 32] {
+        let programs = components.iter()
+            .map(|component| (component.program_hash, component.backend))
+            .collect::<Vec<_>>();
+        sp_io::hashing::blake2_256(&programs.encode())
     }
 
-    fn authority_len() -> u32 {
-        Self::keys().len() as u32
+    /// Whether the proofs of `backend` are verified the same way by every node. The tasks of the
+    /// other backends would never be settled, so they are refused.
+    pub fn is_supported_backend(backend: Backend) -> bool {
+        matches!(backend, Backend::Distaff)
+    }
+
+    /// The payload a subject signs to authorize a task. It is bound to the genesis hash of the
+    /// chain, so that the authorization can not be replayed on another one.
+    pub fn authorization_payload(authorization: &TaskAuthorization<T::BlockNumber>) -> Vec<u8> {
+        let genesis_hash = <frame_system::Pallet<T>>::block_hash(T::BlockNumber::zero());
+        (b"zcloak/task-authorization", genesis_hash, authorization).encode()
+    }
+
+    /// Whether `subject` can create a task for `class`: it has none, or the attestation left
+    /// by its last one is revoked.
+    fn can_create_task(subject: &T::AccountId, class: &Class) -> bool {
+        !TaskParams::<T>::contains_key(subject, class) || RevokedAttestations::<T>::contains_key(subject, class)
+    }
+
+    /// Remove the task of `subject` for `class` if its attestation is revoked, along with its
+    /// `SettledTasks` entry, instead of waiting for it to expire.
+    fn clear_revoked_task(subject: &T::AccountId, class: &Class) {
+        if RevokedAttestations::<T>::take(subject, class).is_none() {
+            return;
+        }
+        if let Some(attestation) = Attestations::<T>::take(subject, class) {
+            SettledTasks::<T>::remove(attestation.expires_at, (subject.clone(), class.clone()));
+        }
+        TaskParams::<T>::remove(subject, class);
+        TaskBundles::<T>::remove(subject, class);
+    }
+
+    /// Create the task of `subject` described by `info`, paid for by `payer`.
+    #[transactional]
+    fn do_create_task(
+        payer: &T::AccountId,
+        subject: &T::AccountId,
+        class: Class,
+        info: TaskInfo,
+        priority_fee: Option<BalanceOf<T>>,
+    ) -> DispatchResult {
+        // Ensure task has not been created
+        ensure!(Self::can_create_task(subject, &class), Error::<T>::TaskAlreadyExists);
+        ensure!(Self::is_supported_backend(info.backend), Error::<T>::UnsupportedBackend);
+        // A registered class can only be proved by its own program
+        if let Some(class_info) = Self::classes(&class) {
+            ensure!(class_info.program_hash == info.program_hash, Error::<T>::ProgramMismatch);
+        }
+        Self::note_new_task(payer, subject, &class)?;
+        let priority_fee = priority_fee.unwrap_or_else(Zero::zero);
+        let fee = T::TaskFee::get().saturating_add(priority_fee);
+        T::Currency::transfer(payer, &Self::account_id(), fee, ExistenceRequirement::KeepAlive)?;
+        Self::clear_revoked_task(subject, &class);
+        let program_hash = info.program_hash;
+        <TaskFees<T>>::insert(subject, &class, fee);
+        <TaskPriority<T>>::insert(subject, &class, (priority_fee, <frame_system::Pallet<T>>::block_number()));
+        <TaskParams<T>>::insert(subject, &class, info);
+        <OngoingTasks<T>>::insert(subject, &class, Status::default());
+        Self::deposit_event(Event::TaskCreated(program_hash));
+        Ok(())
+    }
+
+    /// Count a new task of `who` for `class`, paid for by `payer`, unless it exceeds their quotas.
+    /// The tasks paid for by exempted accounts, e.g. by the relayer of a trusted issuer, are
+    /// counted but never refused.
+    fn note_new_task(payer: &T::AccountId, who: &T::AccountId, class: &Class) -> DispatchResult {
+        let window = T::QuotaWindow::get();
+        let now = <frame_system::Pallet<T>>::block_number();
+        let window_start = if window.is_zero() { Zero::zero() } else { now - now % window };
+        let exempt = Self::quota_exempt(payer);
+
+        let mut account_quota = Self::account_quota(who).at(window_start);
+        ensure!(
+            exempt || account_quota.within(T::MaxOpenTasks::get(), T::MaxTasksPerWindow::get()),
+            Error::<T>::AccountQuotaExceeded
+        );
+        let mut class_quota = Self::class_quota(class).at(window_start);
+        ensure!(
+            exempt || class_quota.within(T::MaxOpenClassTasks::get(), T::MaxClassTasksPerWindow::get()),
+            Error::<T>::ClassQuotaExceeded
+        );
+
+        account_quota.open();
+        class_quota.open();
+        <AccountQuota<T>>::insert(who, account_quota);
+        <ClassQuota<T>>::insert(class, class_quota);
+        Ok(())
+    }
+
+    /// Release the quotas taken by a task once it is settled.
+    fn note_closed_task(who: &T::AccountId, class: &Class) {
+        <AccountQuota<T>>::mutate(who, |quota| quota.close());
+        <ClassQuota<T>>::mutate(class, |quota| quota.close());
+    }
+
+    /// The account of the reward pool
+    pub fn account_id() -> T::AccountId {
+        T::PalletId::get().into_account()
+    }
+
+    /// Store the outcome of a task until `expiration`, reward its verifiers and notify
+    /// the subscribers of `OnTaskSettled` or `OnTaskRejected`.
+    fn settle_task(account: T::AccountId, class: Class, passed: bool, expiration: T::BlockNumber) {
+        let program_hash = Self::task_params(&account, &class).program_hash;
+        <TaskPriority<T>>::remove(&account, &class);
+        <InsecureVotes<T>>::remove(&account, &class);
+        sp_io::offchain_index::clear(&Self::proof_index_key(&account, &class));
+        Self::note_closed_task(&account, &class);
+        Self::reward_verifiers(&account, &class, passed);
+        Self::record_settlement(&account, &class, program_hash, passed, expiration);
+        if passed {
+            T::OnTaskSettled::on_task_settled(&account, &class, &program_hash);
+        } else {
+            T::OnTaskRejected::on_task_rejected(&account, &class, &program_hash);
+        }
+    }
+
+    /// Store the outcome of a task in `SettledTasks` and `Attestations` until `expiration`.
+    fn record_settlement(
+        account: &T::AccountId,
+        class: &Class,
+        program_hash: [u8; 32],
+        passed: bool,
+        expiration: T::BlockNumber,
+    ) {
+        SettledTasks::<T>::insert(expiration, (account.clone(), class.clone()), passed);
+        Attestations::<T>::insert(account, class, Attestation {
+            program_hash,
+            passed,
+            settled_at: <frame_system::Pallet<T>>::block_number(),
+            expires_at: expiration,
+        });
+    }
+
+    /// Share the fee of a settled task among the verifiers whose vote matched the outcome.
+    /// Whatever can not be shared evenly is left in the reward pool.
+    fn reward_verifiers(account: &T::AccountId, class: &Class, passed: bool) {
+        let fee = <TaskFees<T>>::take(account, class);
+        let winners = <TaskVoters<T>>::take(account, class)
+            .into_iter()
+            .filter(|(_, vote)| *vote == passed)
+            .map(|(verifier, _)| verifier)
+            .collect::<Vec<_>>();
+        if winners.is_empty() || fee.is_zero() {
+            return;
+        }
+        let share = fee / BalanceOf::<T>::from(winners.len() as u32);
+        for verifier in winners {
+            <PendingRewards<T>>::mutate(&verifier, |reward| *reward = reward.saturating_add(share));
+            Self::deposit_event(Event::RewardCredited(verifier, share));
+        }
+    }
+
+    /// The number of verifiers of the current session which are not disabled
+    fn active_authority_len() -> u32 {
+        (Self::keys().len() as u32).saturating_sub(Self::disabled_verifiers().len() as u32)
+    }
+
+    /// Whether `authority_id` is one of the verifiers disabled in the current session
+    fn is_disabled(authority_id: &T::AuthorityId) -> bool {
+        let disabled = Self::disabled_verifiers();
+        Self::keys().iter()
+            .position(|key| key == authority_id)
+            .map_or(false, |index| disabled.binary_search(&(index as u32)).is_ok())
     }
 
     fn initialize_keys(keys: &[T::AuthorityId]) {
@@ -629,13 +1817,54 @@ impl<T: Config> Pallet<T> {
         }
     }
 
+    /// Remember the key set of `session_index` and forget the ones older than `HistoryDepth`.
+    fn note_session_keys(session_index: SessionIndex, keys: Vec<(T::AccountId, T::AuthorityId)>) {
+        HistoricalKeys::<T>::insert(session_index, keys);
+        if let Some(stale) = session_index.checked_sub(T::HistoryDepth::get()) {
+            HistoricalKeys::<T>::remove(stale);
+        }
+    }
+
     fn set_keys(keys: &Vec<T::AuthorityId>) {
-        Keys::<T>::put(&keys)
+        Keys::<T>::put(&keys);
+        let validators = T::ValidatorSet::validators().into_iter()
+            .zip(keys.iter().cloned())
+            .collect();
+        Self::note_session_keys(T::ValidatorSet::session_index(), validators);
     }
 }
 
 
 
+impl<T: Config> VerificationProvider<T::AccountId, T::BlockNumber> for Pallet<T> {
+    fn is_verified(who: &T::AccountId, class: &Class, program_hash: &[u8; 32]) -> bool {
+        Self::attestation_of(who, class)
+            .map_or(false, |attestation| attestation.passed && &attestation.program_hash == program_hash)
+            && Self::revoked_at(who, class).is_none()
+    }
+
+    fn attestation_of(who: &T::AccountId, class: &Class) -> Option<Attestation<T::BlockNumber>> {
+        Self::attestations(who, class)
+    }
+
+    fn outputs_of(who: &T::AccountId, class: &Class) -> Option<Vec<Vec<u128>>> {
+        Self::attestation_of(who, class)
+            .filter(|attestation| attestation.passed)
+            .map(|_| {
+                let bundle = Self::task_bundles(who, class);
+                if bundle.is_empty() {
+                    vec![Self::task_params(who, class).outputs]
+                } else {
+                    bundle.into_iter().map(|component| component.outputs).collect()
+                }
+            })
+    }
+
+    fn revoked_at(who: &T::AccountId, class: &Class) -> Option<T::BlockNumber> {
+        Self::revoked_attestations(who, class)
+    }
+}
+
 impl<T: Config> sp_runtime::BoundToRuntimeAppPublic for Pallet<T> {
     type Public = T::AuthorityId;
 }
@@ -646,8 +1875,10 @@ impl<T: Config> OneSessionHandler<T::AccountId> for Pallet<T> {
     fn on_genesis_session<'a, I: 'a>(validators: I)
         where I: Iterator<Item=(&'a T::AccountId, T::AuthorityId)>
     {
-        let keys = validators.map(|x| x.1).collect::<Vec<_>>();
+        let validators = validators.map(|(account, key)| (account.clone(), key)).collect::<Vec<_>>();
+        let keys = validators.iter().map(|x| x.1.clone()).collect::<Vec<_>>();
         Self::initialize_keys(&keys);
+        Self::note_session_keys(T::ValidatorSet::session_index(), validators);
     }
 
     fn on_new_session<'a, I: 'a>(_changed: bool, validators: I, _queued_validators: I)
@@ -655,10 +1886,19 @@ impl<T: Config> OneSessionHandler<T::AccountId> for Pallet<T> {
     {
 
         // Remember who the authorities are for the new session.
-        Keys::<T>::put(validators.map(|x| x.1).collect::<Vec<_>>());
+        let validators = validators.map(|(account, key)| (account.clone(), key)).collect::<Vec<_>>();
+        Keys::<T>::put(validators.iter().map(|x| x.1.clone()).collect::<Vec<_>>());
+        Self::note_session_keys(T::ValidatorSet::session_index(), validators);
+        // Verifiers are only disabled for the rest of a session.
+        DisabledVerifiers::<T>::kill();
     }
 
-    fn on_disabled(_i: usize) {
-        // Ignore
+    fn on_disabled(i: usize) {
+        let index = i as u32;
+        DisabledVerifiers::<T>::mutate(|disabled| {
+            if let Err(location) = disabled.binary_search(&index) {
+                disabled.insert(location, index);
+            }
+        });
     }
 }
@@ -0,0 +1,208 @@
+//! # Storage migrations of the Starks-verifier Module
+//!
+//! Every change to a stored type bumps `Releases` and adds a module here which moves the
+//! storage from the previous release to the new one. `migrate` applies them in order,
+//! starting from the release found in `StorageVersion`.
+//!
+//! Each step comes with a `pre_migrate` and a `post_migrate` check, which are run around
+//! the upgrade by `try-runtime` against a snapshot of a live chain, and by the tests
+//! against a state exported from a chain running the old release.
+
+use super::*;
+use frame_support::{traits::Get, weights::Weight};
+
+/// Migrate the storage from the release in `StorageVersion` to `Releases::LATEST`.
+pub fn migrate<T: Config>() -> Weight {
+    let mut weight = T::DbWeight::get().reads(1);
+    if StorageVersion::<T>::get() < Releases::V2 {
+        weight = weight.saturating_add(v2::migrate::<T>());
+    }
+    weight
+}
+
+/// Check that the storage can be migrated. Run before `migrate`.
+#[cfg(any(feature = "try-runtime", test))]
+pub fn pre_migrate<T: Config>() -> Result<(), &'static str> {
+    if StorageVersion::<T>::get() < Releases::V2 {
+        v2::pre_migrate::<T>()?;
+    }
+    Ok(())
+}
+
+/// Check that the storage is in the layout of `Releases::LATEST`. Run after `migrate`.
+#[cfg(any(feature = "try-runtime", test))]
+pub fn post_migrate<T: Config>() -> Result<(), &'static str> {
+    if StorageVersion::<T>::get() != Releases::LATEST {
+        return Err("The storage version is not the latest one");
+    }
+    v2::post_migrate::<T>()
+}
+
+/// The raw values stored under `prefix`, whatever type they are encoded in.
+#[cfg(any(feature = "try-runtime", test))]
+fn raw_values(prefix: &[u8]) -> Vec<Vec<u8>> {
+    let mut values = Vec::new();
+    let mut key = prefix.to_vec();
+    while let Some(next) = sp_io::storage::next_key(&key) {
+        if !next.starts_with(prefix) {
+            break;
+        }
+        values.extend(frame_support::storage::unhashed::get_raw(&next));
+        key = next;
+    }
+    values
+}
+
+/// `V1` to `V2`:
+/// - votes on ongoing tasks are recorded by the account of the verifier instead of its index
+///   into `Keys`, and the key set of the current session is kept in `HistoricalKeys`;
+/// - every task declares the `Backend` its program is proved with, and the tasks created
+///   before were all proved with distaff;
+/// - the outcome of the settled tasks is indexed by subject and class in `Attestations`;
+/// - the open tasks are counted in `AccountQuota` and `ClassQuota`.
+pub mod v2 {
+    use super::*;
+    #[cfg(any(feature = "try-runtime", test))]
+    use frame_support::storage::StoragePrefixedMap;
+
+    /// The status of a task in `V1`
+    #[derive(Encode, Decode, Default, PartialEq, Eq, RuntimeDebug)]
+    pub struct OldStatus {
+        // The indexes into `Keys` of the verifiers involved so far
+        pub verifiers: Vec<u32>,
+        // The number of affirmative vote so far
+        pub ayes: u32,
+        // The number of dissenting vote so far
+        pub nays: u32
+    }
+
+    /// The info of a task in `V1`
+    #[derive(Encode, Decode, Default, Clone, PartialEq, Eq, RuntimeDebug)]
+    pub struct OldTaskInfo {
+        // The id of the proof
+        pub proof_id: Vec<u8>,
+        // Inputs of the task
+        pub inputs: Vec<u128>,
+        // Outputs of the task
+        pub outputs: Vec<u128>,
+        // The hash of the program
+        pub program_hash: [u8; 32],
+    }
+
+    impl From<OldTaskInfo> for TaskInfo {
+        fn from(old: OldTaskInfo) -> Self {
+            TaskInfo {
+                proof_id: old.proof_id,
+                inputs: old.inputs,
+                outputs: old.outputs,
+                program_hash: old.program_hash,
+                backend: Backend::Distaff,
+            }
+        }
+    }
+
+    pub fn migrate<T: Config>() -> Weight {
+        // `V1` did not keep the key sets of past sessions, so the best the indexes can be
+        // resolved against is the key set of the current session.
+        let validators = T::ValidatorSet::validators().into_iter()
+            .zip(Keys::<T>::get().into_iter())
+            .collect::<Vec<_>>();
+        let mut ongoing = 0u64;
+        OngoingTasks::<T>::translate::<OldStatus, _>(|account, class, old| {
+            ongoing += 1;
+            AccountQuota::<T>::mutate(&account, |quota| quota.open = quota.open.saturating_add(1));
+            ClassQuota::<T>::mutate(&class, |quota| quota.open = quota.open.saturating_add(1));
+            Some(Status {
+                verifiers: old.verifiers.into_iter()
+                    .filter_map(|index| validators.get(index as usize).map(|(account, _)| account.clone()))
+                    .collect(),
+                ayes: old.ayes,
+                nays: old.nays,
+            })
+        });
+
+        // Keep the receipts signed in the current session valid.
+        HistoricalKeys::<T>::insert(T::ValidatorSet::session_index(), validators);
+
+        let mut tasks = 0u64;
+        TaskParams::<T>::translate::<OldTaskInfo, _>(|_, _, old| {
+            tasks += 1;
+            Some(old.into())
+        });
+
+        let mut settled = 0u64;
+        for (expiration, (account, class), passed) in SettledTasks::<T>::iter() {
+            settled += 1;
+            Attestations::<T>::insert(&account, &class, Attestation {
+                program_hash: TaskParams::<T>::get(&account, &class).program_hash,
+                passed,
+                settled_at: expiration.saturating_sub(T::StorePeriod::get()),
+                expires_at: expiration,
+            });
+        }
+
+        StorageVersion::<T>::put(Releases::V2);
+        log::info!(
+            target: "starks-verifier",
+            "Migrated {} ongoing tasks, {} tasks and {} settled tasks to V2",
+            ongoing,
+            tasks,
+            settled,
+        );
+
+        T::DbWeight::get().reads_writes(3 * ongoing + tasks + 2 * settled + 2, 3 * ongoing + tasks + settled + 2)
+    }
+
+    #[cfg(any(feature = "try-runtime", test))]
+    pub fn pre_migrate<T: Config>() -> Result<(), &'static str> {
+        let keys_len = Keys::<T>::decode_len().unwrap_or_default() as u32;
+        for raw in raw_values(&OngoingTasks::<T>::final_prefix()) {
+            let status = OldStatus::decode(&mut &raw[..])
+                .map_err(|_| "An ongoing task can not be decoded in the V1 layout")?;
+            if status.verifiers.iter().any(|index| *index >= keys_len) {
+                return Err("An ongoing task is voted by a verifier out of the current key set");
+            }
+        }
+        for raw in raw_values(&TaskParams::<T>::final_prefix()) {
+            OldTaskInfo::decode(&mut &raw[..])
+                .map_err(|_| "A task can not be decoded in the V1 layout")?;
+        }
+        for (_, (account, class), _) in SettledTasks::<T>::iter() {
+            if !TaskParams::<T>::contains_key(&account, &class) {
+                return Err("A settled task has no params");
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(any(feature = "try-runtime", test))]
+    pub fn post_migrate<T: Config>() -> Result<(), &'static str> {
+        for raw in raw_values(&OngoingTasks::<T>::final_prefix()) {
+            Status::<T::AccountId>::decode(&mut &raw[..])
+                .map_err(|_| "An ongoing task can not be decoded in the V2 layout")?;
+        }
+        if !HistoricalKeys::<T>::contains_key(T::ValidatorSet::session_index()) {
+            return Err("The key set of the current session is missing");
+        }
+        for raw in raw_values(&TaskParams::<T>::final_prefix()) {
+            TaskInfo::decode(&mut &raw[..])
+                .map_err(|_| "A task can not be decoded in the V2 layout")?;
+        }
+        for raw in raw_values(&TaskBundles::<T>::final_prefix()) {
+            Vec::<TaskInfo>::decode(&mut &raw[..])
+                .map_err(|_| "A bundle can not be decoded in the V2 layout")?;
+        }
+        for (expiration, (account, class), passed) in SettledTasks::<T>::iter() {
+            match Attestations::<T>::get(&account, &class) {
+                Some(attestation) if attestation.passed == passed && attestation.expires_at == expiration => {},
+                _ => return Err("A settled task has no matching attestation"),
+            }
+        }
+        let open = OngoingTasks::<T>::iter().count() as u32;
+        let counted = AccountQuota::<T>::iter_values().map(|quota| quota.open).sum::<u32>();
+        if open != counted {
+            return Err("The open tasks are not counted in AccountQuota");
+        }
+        Ok(())
+    }
+}
@@ -5,10 +5,10 @@ use crate::Config;
 use sp_runtime::Perbill;
 use sp_staking::SessionIndex;
 use pallet_session::historical as pallet_session_historical;
-use sp_runtime::testing::{Header, UintAuthorityId, TestXt};
+use sp_runtime::testing::{Header, UintAuthorityId, TestSignature, TestXt};
 use sp_runtime::traits::{IdentityLookup, BlakeTwo256, ConvertInto};
 use sp_core::H256;
-use frame_support::parameter_types;
+use frame_support::{parameter_types, PalletId};
 use crate as verifier;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -22,6 +22,7 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		Session: pallet_session::{Pallet, Call, Storage, Event, Config<T>},
 		Historical: pallet_session_historical::{Pallet},
 		Verifier: verifier::{Pallet, Call, Storage, Config<T>, Event<T>, ValidateUnsigned},
@@ -36,6 +37,23 @@ thread_local! {
 	]));
 }
 
+thread_local! {
+	pub static SETTLED: RefCell<Vec<(u64, Class, bool)>> = RefCell::new(vec![]);
+}
+
+/// Records the outcome of the settled tasks into `SETTLED`.
+pub struct RecordOutcome;
+impl OnTaskSettled<u64> for RecordOutcome {
+	fn on_task_settled(who: &u64, class: &Class, _program_hash: &[u8; 32]) {
+		SETTLED.with(|l| l.borrow_mut().push((*who, class.clone(), true)));
+	}
+}
+impl OnTaskRejected<u64> for RecordOutcome {
+	fn on_task_rejected(who: &u64, class: &Class, _program_hash: &[u8; 32]) {
+		SETTLED.with(|l| l.borrow_mut().push((*who, class.clone(), false)));
+	}
+}
+
 pub struct TestSessionManager;
 impl pallet_session::SessionManager<u64> for TestSessionManager {
 	fn new_session(_new_index: SessionIndex) -> Option<Vec<u64>> {
@@ -74,7 +92,7 @@ impl frame_system::Config for Test {
 	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -82,6 +100,20 @@ impl frame_system::Config for Test {
 	type OnSetCode = ();
 }
 
+parameter_types! {
+	pub const ExistentialDeposit: u64 = 1;
+}
+
+impl pallet_balances::Config for Test {
+	type MaxLocks = ();
+	type Balance = u64;
+	type Event = Event;
+	type DustRemoval = ();
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+}
+
 parameter_types! {
 	pub const Period: u64 = 1;
 	pub const Offset: u64 = 0;
@@ -112,14 +144,44 @@ impl pallet_session::historical::Config for Test {
 parameter_types! {
 	pub const UnsignedPriority: u64 = 1 << 20;
 	pub const StoragePeriod: u64 = 20;
+	pub const TaskFee: u64 = 10;
+	pub const HistoryDepth: SessionIndex = 3;
+	pub const MaxOpenTasks: u32 = 2;
+	pub const MaxOpenClassTasks: u32 = 2;
+	pub const QuotaWindow: u64 = 10;
+	pub const MaxTasksPerWindow: u32 = 3;
+	pub const MaxClassTasksPerWindow: u32 = 3;
+	pub const MaxBundleSize: u32 = 2;
+	pub const MinSecurityLevel: u32 = 0;
+	pub const MaxOnChainProofSize: u32 = 64 * 1024;
+	pub const MaxIndexedProofSize: u32 = 128 * 1024;
+	pub const VerifierPalletId: PalletId = PalletId(*b"zk/verif");
 }
 
 impl Config for Test {
 	type Event = Event;
 	type AuthorityId = UintAuthorityId;
 	type ValidatorSet = Historical;
+	type HistoryDepth = HistoryDepth;
 	type StorePeriod = StoragePeriod;
 	type UnsignedPriority = UnsignedPriority;
+	type Currency = Balances;
+	type TaskFee = TaskFee;
+	type PalletId = VerifierPalletId;
+	type OnTaskSettled = (RecordOutcome, ());
+	type OnTaskRejected = (RecordOutcome, ());
+	type MaxOpenTasks = MaxOpenTasks;
+	type MaxOpenClassTasks = MaxOpenClassTasks;
+	type QuotaWindow = QuotaWindow;
+	type MaxTasksPerWindow = MaxTasksPerWindow;
+	type MaxClassTasksPerWindow = MaxClassTasksPerWindow;
+	type MaxBundleSize = MaxBundleSize;
+	type MinSecurityLevel = MinSecurityLevel;
+	type MaxOnChainProofSize = MaxOnChainProofSize;
+	type MaxIndexedProofSize = MaxIndexedProofSize;
+	type WeightInfo = ();
+	type OffchainSignature = TestSignature;
+	type OffchainPublic = UintAuthorityId;
 }
 
 impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test where
@@ -132,9 +194,12 @@ impl<LocalCall> frame_system::offchain::SendTransactionTypes<LocalCall> for Test
 
 
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	let t = frame_system::GenesisConfig::default()
+	let mut t = frame_system::GenesisConfig::default()
 		.build_storage::<Test>()
 		.unwrap();
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (3, 100)],
+	}.assimilate_storage(&mut t).unwrap();
 	t.into()
 }
 
@@ -1,6 +1,3 @@
-use core::slice;
-use std::convert::TryInto;
-
 use super::*;
 use crate::mock::*;
 use sp_core::{traits::TaskExecutorExt, testing::TaskExecutor};
@@ -11,10 +8,11 @@ use sp_core::offchain::{
 	testing::{self as testing, TestOffchainExt, TestTransactionPoolExt},
 };
 use sp_core::H256;
-use frame_support::{dispatch, assert_ok, assert_noop, traits::OnFinalize};
-use sp_runtime::{testing::UintAuthorityId, transaction_validity::TransactionValidityError};
+use frame_support::{dispatch, assert_ok, assert_noop, traits::{GenesisBuild, OnFinalize}};
+use sp_runtime::testing::UintAuthorityId;
 use frame_support::traits::OffchainWorker;
 use sp_runtime::testing::TestSignature;
+use sp_runtime::Perbill;
 
 
 #[test]
@@ -53,21 +51,27 @@ fn should_create_task() {
 	let mut ext = new_test_ext();
 
 	ext.execute_with( || {
-		let (progam_hash, inputs, outputs, proof_id) = task_params();
-		Verifier::create_task(Origin::signed(1), progam_hash, inputs.clone(), outputs.clone(), proof_id.clone());
+		let class = b"class".to_vec();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		assert_ok!(Verifier::create_task(
+			Origin::signed(1), class.clone(), program_hash.into(), Backend::Distaff, inputs.clone(), outputs.clone(),
+			proof_id.clone(), None, None,
+		));
 		assert_eq!(
-			Verifier::task_params(&progam_hash),
+			Verifier::task_params(1, &class),
 			TaskInfo {
 				proof_id: b"QmSmn1rSSXmu1PyFFTosBtcL2KGzEssetk9MVFYyDHoCGa".to_vec(),
 				inputs,
-				outputs
+				outputs,
+				program_hash: program_hash.into(),
+				backend: Backend::Distaff,
 			}
 		);
 
 		assert_eq!(
-			Verifier::ongoing_tasks(&progam_hash),
+			Verifier::ongoing_tasks(1, &class),
 			Some(Status {
-				verifiers: Vec::<u32>::new(),
+				verifiers: Vec::<u64>::new(),
 				ayes: 0,
 				nays: 0
 			})
@@ -78,13 +82,13 @@ fn should_create_task() {
 #[test]
 fn should_parse_http_response() {
 	let (offchain, offchain_state) = TestOffchainExt::new();
-	let mut ext = sp_io::TestExternalities::default();
+	let mut ext = new_test_ext();
 	
 	ext.register_extension(OffchainDbExt::new(offchain.clone()));
 	ext.register_extension(OffchainWorkerExt::new(offchain));
 	ext.register_extension(TaskExecutorExt::new(TaskExecutor::new()));
 
-	let (program_hash, inputs, outputs, proof_id) = task_params();
+	let (.., proof_id) = task_params();
 
 	{
 		let mut state = offchain_state.write();
@@ -100,40 +104,180 @@ fn should_parse_http_response() {
 
 	ext.execute_with(|| {
 		set_key_and_tasks();
-		let proof = Verifier::fetch_proof(&program_hash);
+		let proof = Verifier::fetch_proof(&(1, b"class".to_vec()), &proof_id);
 		assert_eq!(proof.unwrap(), new_proof().unwrap());
 	});
 }
 
 
+#[test]
+fn should_fetch_indexed_proofs_first() {
+	let (offchain, _offchain_state) = TestOffchainExt::new();
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+
+	ext.execute_with(|| {
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		let class = b"indexed".to_vec();
+		let proof = new_proof().unwrap();
+		assert_noop!(
+			Verifier::create_task(
+				Origin::signed(1), class.clone(), program_hash.into(), Backend::Distaff, inputs.clone(), outputs.clone(),
+				proof_id.clone(), Some(vec![0u8; MaxIndexedProofSize::get() as usize + 1]), None,
+			),
+			Error::<Test>::ProofTooLarge,
+		);
+		assert_ok!(Verifier::create_task(
+			Origin::signed(1), class.clone(), program_hash.into(), Backend::Distaff, inputs, outputs,
+			proof_id.clone(), Some(proof.clone()), None,
+		));
+
+		// What a node indexing the transactions writes into its offchain DB
+		let task = (1, class);
+		StorageValueRef::persistent(&Verifier::proof_index_key(&task.0, &task.1))
+			.set(&(proof_id.clone(), proof.clone()));
+		// No request is expected, so the proof can only come from the offchain DB
+		assert_eq!(Verifier::fetch_proof(&task, &proof_id).unwrap(), proof);
+		// The proof indexed for another id is not used
+		assert_eq!(Verifier::indexed_proof(&task, &b"another proof".to_vec()), None);
+	});
+}
+
+#[test]
+fn should_only_read_the_local_proof_store_offchain() {
+	let store = sp_starks::proof_store::open(&std::env::temp_dir().join("starks-verifier-tests")).unwrap();
+	let proof_id = store.put(b"proof").unwrap().into_bytes();
+
+	// the files of a node are out of reach of block execution
+	new_test_ext().execute_with(|| {
+		assert_eq!(sp_starks::starks::fetch_local_proof(&proof_id), None);
+	});
+
+	let (offchain, _offchain_state) = TestOffchainExt::new();
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+	ext.execute_with(|| {
+		assert_eq!(sp_starks::starks::fetch_local_proof(&proof_id), Some(b"proof".to_vec()));
+	});
+}
+
 #[test]
 fn basic_starks_verifier_works() {
 	new_test_ext().execute_with(|| {
 		// get proof
 		let proof = new_proof().unwrap();
-		let (program_hash, inputs, outputs, proof_id) = task_params();
-		let program_hash = [19, 23, 145, 150, 7, 226, 183, 94, 42, 36, 220, 169, 148, 89, 125, 153, 113, 250, 202, 142, 187, 167, 14, 144, 186, 217, 89, 214, 222, 234, 43, 214];
-		let res = sp_starks::starks::verify(&program_hash, &inputs, &outputs, &proof);
-		assert!(res.is_ok());
+		let (program_hash, inputs, outputs, _) = task_params();
+		let res = sp_starks::starks::verify(Backend::Distaff, &program_hash.into(), &inputs, &outputs, &proof);
+		assert_eq!(res, Ok(true));
 	});
 }
 
 
+// A xorshift generator, so that the fuzzed proofs are the same on every run
+fn next_random(state: &mut u64) -> u64 {
+	*state ^= *state << 13;
+	*state ^= *state >> 7;
+	*state ^= *state << 17;
+	*state
+}
+
+fn verify_fuzzed(proof: &[u8]) -> Result<bool, VerifyError> {
+	let (program_hash, inputs, outputs, _) = task_params();
+	sp_starks::starks::verify(Backend::Distaff, &program_hash.into(), &inputs, &outputs, proof)
+}
+
+#[test]
+fn should_not_panic_on_truncated_proofs() {
+	new_test_ext().execute_with(|| {
+		let proof = new_proof().unwrap();
+		assert_eq!(verify_fuzzed(&proof), Ok(true));
+		let mut seed = 0x2545_f491_4f6c_dd1d;
+		for _ in 0..64 {
+			let len = next_random(&mut seed) as usize % proof.len();
+			assert_eq!(verify_fuzzed(&proof[..len]), Err(VerifyError::MalformedProof), "truncated at {}", len);
+		}
+	});
+}
+
+#[test]
+fn should_not_panic_on_random_proofs() {
+	new_test_ext().execute_with(|| {
+		let proof = new_proof().unwrap();
+		let mut seed = 0x9e37_79b9_7f4a_7c15;
+		for _ in 0..64 {
+			// random bytes
+			let len = next_random(&mut seed) as usize % 1024;
+			let random = (0..len).map(|_| next_random(&mut seed) as u8).collect::<Vec<_>>();
+			assert!(matches!(
+				verify_fuzzed(&random),
+				Err(VerifyError::MalformedProof) | Err(VerifyError::InvalidProof)
+			));
+
+			// a corrupted byte in the proof
+			let mut corrupted = proof.clone();
+			let index = next_random(&mut seed) as usize % corrupted.len();
+			corrupted[index] ^= (next_random(&mut seed) as u8) | 1;
+			assert_ne!(verify_fuzzed(&corrupted), Ok(true), "corrupted at {}", index);
+		}
+		assert_eq!(verify_fuzzed(&vec![0u8; sp_starks::MAX_PROOF_SIZE + 1]), Err(VerifyError::ResourceLimit));
+	});
+}
+
+#[test]
+fn should_refuse_unsupported_backends() {
+	new_test_ext().execute_with(|| {
+		let (program_hash, inputs, outputs, _) = task_params();
+		let proof = new_proof().unwrap();
+		assert_eq!(
+			sp_starks::starks::verify(Backend::Miden, &program_hash.into(), &inputs, &outputs, &proof),
+			Err(VerifyError::UnsupportedVersion),
+		);
+	});
+}
+
+#[test]
+fn should_reject_tasks_of_unsupported_backends() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let class = b"miden".to_vec();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		let component = TaskInfo {
+			proof_id: proof_id.clone(), inputs: inputs.clone(), outputs: outputs.clone(), program_hash: program_hash.into(),
+			backend: Backend::Miden,
+		};
+		assert_noop!(
+			Verifier::create_task(
+				Origin::signed(1), class.clone(), program_hash.into(), Backend::Miden, inputs, outputs, proof_id, None, None
+			),
+			Error::<Test>::UnsupportedBackend,
+		);
+		assert_noop!(
+			Verifier::create_bundle(Origin::signed(1), class.clone(), vec![component], None),
+			Error::<Test>::UnsupportedBackend,
+		);
+		// neither a fee nor a quota is taken
+		assert_eq!(Balances::free_balance(1), 100);
+		assert_eq!(Verifier::account_quota(1).open, 0);
+	});
+}
+
 #[test]
 fn should_send_extrinsic() {
 	let (offchain, offchain_state) = TestOffchainExt::new();
 	let (pool, pool_state) = TestTransactionPoolExt::new();
-	let mut ext = sp_io::TestExternalities::default();
+	let mut ext = new_test_ext();
 	ext.register_extension(TransactionPoolExt::new(pool));
 	ext.register_extension(OffchainDbExt::new(offchain.clone()));
 	ext.register_extension(OffchainWorkerExt::new(offchain));
 
-	let (program_hash, inputs, outputs, proof_id) = task_params();
+	let (program_hash, ..) = task_params();
 	three_http_request(&mut offchain_state.write());
 
 	ext.execute_with(|| {
+		let class = b"class".to_vec();
 		UintAuthorityId::set_all_keys(vec![1, 2, 3]);
-		assert_eq!(Verifier::ongoing_tasks(&program_hash), None);
+		assert_eq!(Verifier::ongoing_tasks(1, &class), None);
 		set_key_and_tasks();
 		assert_eq!(Session::validators(), vec![1, 2, 3]);
 		assert_eq!(Verifier::keys(), vec![UintAuthorityId(1), UintAuthorityId(2), UintAuthorityId(3)]);
@@ -141,94 +285,717 @@ fn should_send_extrinsic() {
 		// Offchain worker
 		Verifier::offchain_worker(System::block_number());
 
-		// then
-		let transaction = pool_state.write().transactions.pop().unwrap();
-		// All validators have `0` as their session key, so we generate 2 transactions.
-		assert_eq!(pool_state.read().transactions.len(), 2);
+		// All three keys are local, so we generate 3 transactions.
+		assert_eq!(pool_state.read().transactions.len(), 3);
+		let submissions = pool_state.write().transactions.drain(..).map(|transaction| {
+			let ex: Extrinsic = Decode::decode(&mut &*transaction).unwrap();
+			match ex.call {
+				crate::mock::Call::Verifier(crate::Call::submit_verification(receipt, signature)) => (receipt, signature),
+				e => panic!("Unexpected call: {:?}", e),
+			}
+		}).collect::<Vec<_>>();
 
-		// check the transaction
-		let ex: Extrinsic = Decode::decode(&mut &*transaction).unwrap();
-		let receipt = match ex.call {
-			crate::mock::Call::Verifier(crate::Call::submit_verification(r, ..)) => r,
-			e => panic!("Unexpected call: {:?}", e),
-		};
+		// check the transactions
+		for (auth_index, (receipt, _)) in submissions.iter().enumerate() {
+			assert_eq!(receipt, &VerificationReceipt {
+				task_tuple_id: (1, class.clone()),
+				program_hash: program_hash.into(),
+				passed: true,
+				insecure: false,
+				submit_at: System::block_number(),
+				auth_index: auth_index as u32,
+				session_index: Session::current_index(),
+			});
+		}
 
-		// pop the last one
-		assert_eq!(receipt.clone(), VerificationReceipt {
-			program_hash: program_hash,
-    		passed: true,
-    		submit_at: System::block_number(),
-    		auth_index: 2,
-    		validators_len: 3
-		});
-		// test ValidateUnsigned
-		let signature = UintAuthorityId(3).sign(&receipt.clone().encode()).unwrap();
+		// two of the three votes settle the task
+		for (receipt, signature) in submissions.into_iter().take(2) {
+			let call = crate::Call::submit_verification(receipt.clone(), signature.clone());
+			assert_ok!(
+				<Verifier as sp_runtime::traits::ValidateUnsigned>::validate_unsigned(
+					TransactionSource::External,
+					&call
+				)
+			);
+			assert_ok!(Verifier::submit_verification(Origin::none(), receipt, signature));
+		}
+
+		let block_number = System::block_number() + StoragePeriod::get();
+		// check the online status, should be removed
+		assert_eq!(Verifier::ongoing_tasks(1, &class), None);
+		assert!(Verifier::settled_tasks(block_number, (1, class.clone())));
+
+		Verifier::on_finalize(block_number);
+
+		assert!(!Verifier::settled_tasks(block_number, (1, class)));
+	});
+}
+
+#[test]
+fn should_reward_matching_verifiers() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let class = b"reward".to_vec();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		assert_ok!(Verifier::create_task(
+			Origin::signed(1), class.clone(), program_hash.into(), Backend::Distaff, inputs, outputs, proof_id, None, None
+		));
+		assert_eq!(Balances::free_balance(Verifier::account_id()), TaskFee::get());
+
+		// validators 1 and 2 pass the task, validator 3 does not
+		assert_ok!(submit_receipt(0, &class, false));
+		assert_ok!(submit_receipt(1, &class, true));
+		assert_ok!(submit_receipt(2, &class, true));
+
+		assert_eq!(Verifier::pending_rewards(1), 0);
+		assert_eq!(Verifier::pending_rewards(2), TaskFee::get() / 2);
+		assert_eq!(Verifier::pending_rewards(3), TaskFee::get() / 2);
+		assert_eq!(Verifier::task_fees(1, &class), 0);
+
+		assert_ok!(Verifier::claim_rewards(Origin::signed(2)));
+		assert_eq!(Balances::free_balance(2), 100 + TaskFee::get() / 2);
+		assert_eq!(Verifier::pending_rewards(2), 0);
+		assert_noop!(Verifier::claim_rewards(Origin::signed(1)), Error::<Test>::NoRewards);
+	});
+}
+
+#[test]
+fn should_accept_receipts_across_session_rotation() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let class = b"rotation".to_vec();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		assert_ok!(Verifier::create_task(
+			Origin::signed(1), class.clone(), program_hash.into(), Backend::Distaff, inputs, outputs, proof_id, None, None
+		));
+		let session_index = Session::current_index();
+		let receipt = VerificationReceipt {
+			task_tuple_id: (1, class.clone()),
+			program_hash: program_hash.into(),
+			passed: true,
+			insecure: false,
+			submit_at: System::block_number(),
+			auth_index: 0,
+			session_index,
+		};
+		let signature = UintAuthorityId(1).sign(&receipt.encode()).unwrap();
 		let call = crate::Call::submit_verification(receipt.clone(), signature.clone());
-		assert_ok!(
+
+		// a new validator set is enacted before the receipt is included
+		VALIDATORS.with(|l| *l.borrow_mut() = Some(vec![4, 5, 6]));
+		advance_session();
+		advance_session();
+		assert_eq!(Session::validators(), vec![4, 5, 6]);
+		assert_ok!(<Verifier as sp_runtime::traits::ValidateUnsigned>::validate_unsigned(
+			TransactionSource::External,
+			&call,
+		));
+		assert_ok!(Verifier::submit_verification(Origin::none(), receipt.clone(), signature.clone()));
+		assert_eq!(Verifier::ongoing_tasks(1, &class).unwrap().verifiers, vec![1]);
+
+		// the key set of the session is eventually forgotten
+		advance_session();
+		assert_eq!(Verifier::historical_keys(session_index), None);
+		assert_eq!(
 			<Verifier as sp_runtime::traits::ValidateUnsigned>::validate_unsigned(
 				TransactionSource::External,
-				&call
-			)
+				&call,
+			),
+			InvalidTransaction::Stale.into(),
+		);
+	});
+}
+
+#[test]
+fn should_count_verifiers_once_across_key_rotations() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let class = b"rekeyed".to_vec();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		assert_ok!(Verifier::create_task(
+			Origin::signed(1), class.clone(), program_hash.into(), Backend::Distaff, inputs, outputs, proof_id, None, None
+		));
+		assert_ok!(submit_receipt(0, &class, true));
+
+		// validator 1 signs with a new key in the next session
+		advance_session();
+		Verifier::set_keys(&vec![UintAuthorityId(11), UintAuthorityId(2), UintAuthorityId(3)]);
+		assert_eq!(Verifier::historical_keys(Session::current_index()).unwrap()[0], (1, UintAuthorityId(11)));
+		assert_noop!(submit_receipt(0, &class, true), Error::<Test>::DuplicatedSubmission);
+		assert_eq!(Verifier::ongoing_tasks(1, &class).unwrap().verifiers, vec![1]);
+		assert_eq!(Verifier::task_voters(1, &class), vec![(1, true)]);
+	});
+}
+
+#[test]
+fn disabled_verifier_should_not_vote() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let class = b"disabled".to_vec();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		assert_ok!(Verifier::create_task(
+			Origin::signed(1), class.clone(), program_hash.into(), Backend::Distaff, inputs, outputs, proof_id, None, None
+		));
+		Verifier::on_disabled(1);
+		assert_eq!(Verifier::disabled_verifiers(), vec![1]);
+
+		assert_noop!(submit_receipt(1, &class, true), Error::<Test>::VerifierDisabled);
+		// one vote is a majority of the two verifiers left
+		assert_ok!(submit_receipt(0, &class, true));
+		assert_eq!(Verifier::ongoing_tasks(1, &class), None);
+
+		// verifiers are enabled again in the next session
+		advance_session();
+		assert!(Verifier::disabled_verifiers().is_empty());
+	});
+}
+
+#[test]
+fn genesis_config_works() {
+	let (program_hash, inputs, outputs, proof_id) = task_params();
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	crate::GenesisConfig::<Test> {
+		keys: vec![(1, UintAuthorityId(1)), (2, UintAuthorityId(2))],
+		classes: vec![(b"age".to_vec(), 1, program_hash.into())],
+		threshold: Perbill::from_percent(67),
+		settled_tasks: vec![(
+			2,
+			b"age".to_vec(),
+			TaskInfo {
+				proof_id, inputs: inputs.clone(), outputs: outputs.clone(), program_hash: program_hash.into(),
+				backend: Backend::Distaff,
+			},
+			true,
+		)],
+	}.assimilate_storage(&mut t).unwrap();
+	pallet_balances::GenesisConfig::<Test> { balances: vec![(3, 100)] }.assimilate_storage(&mut t).unwrap();
+
+	sp_io::TestExternalities::from(t).execute_with(|| {
+		assert_eq!(Verifier::keys(), vec![UintAuthorityId(1), UintAuthorityId(2)]);
+		assert_eq!(Verifier::historical_keys(0), Some(vec![(1, UintAuthorityId(1)), (2, UintAuthorityId(2))]));
+		assert_eq!(Verifier::vote_threshold(), Perbill::from_percent(67));
+		assert_eq!(Verifier::classes(b"age".to_vec()), Some(ClassInfo { owner: 1, program_hash: program_hash.into() }));
+		assert!(Verifier::settled_tasks(StoragePeriod::get(), (2, b"age".to_vec())));
+
+		// only the registered program can prove the class
+		assert_noop!(
+			Verifier::create_task(
+				Origin::signed(3), b"age".to_vec(), [0u8; 32], Backend::Distaff, inputs, outputs, b"proof".to_vec(), None, None
+			),
+			Error::<Test>::ProgramMismatch,
 		);
+	});
+}
 
-		let _ = Verifier::submit_verification(Origin::none(), receipt, signature);
+#[test]
+fn should_migrate_from_v1() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		// the state exported with `state_getPairs` from a chain of this mock running `V1`,
+		// whose validators are 1, 2 and 3
+		Keys::<Test>::kill();
+		for session_index in 0..=Session::current_index() {
+			HistoricalKeys::<Test>::remove(session_index);
+		}
+		let pairs: Vec<(sp_core::Bytes, sp_core::Bytes)> =
+			serde_json::from_str(include_str!("../v1-state.json")).unwrap();
+		for (key, value) in pairs {
+			frame_support::storage::unhashed::put_raw(&key, &value);
+		}
+		assert_eq!(Verifier::storage_version(), Releases::V1);
+		assert_eq!(Verifier::keys(), vec![UintAuthorityId(1), UintAuthorityId(2), UintAuthorityId(3)]);
+		assert!(migrations::post_migrate::<Test>().is_err());
+
+		assert_ok!(migrations::pre_migrate::<Test>());
+		migrations::migrate::<Test>();
+		assert_ok!(migrations::post_migrate::<Test>());
 
-		// check the online status
-		let status = Verifier::ongoing_tasks(&program_hash);
-		assert_eq!(status, Some(Status {
-			verifiers: vec![2],
-			ayes: 1,
-			nays: 0
+		let (program_hash, ..) = task_params();
+		let (kyc, age) = (b"kyc".to_vec(), b"age".to_vec());
+		assert_eq!(Verifier::storage_version(), Releases::LATEST);
+		assert_eq!(Verifier::ongoing_tasks(1, &kyc), Some(Status { verifiers: vec![1, 3], ayes: 1, nays: 1 }));
+		assert_eq!(Verifier::ongoing_tasks(3, &age), Some(Status { verifiers: vec![2], ayes: 1, nays: 0 }));
+		assert_eq!(
+			Verifier::historical_keys(Session::current_index()),
+			Some(vec![(1, UintAuthorityId(1)), (2, UintAuthorityId(2)), (3, UintAuthorityId(3))]),
+		);
+		for (who, class) in vec![(1, &kyc), (3, &age), (2, &age)] {
+			assert_eq!(Verifier::task_params(who, class).backend, Backend::Distaff);
+		}
+		assert_eq!(Verifier::attestations(2, &age), Some(Attestation {
+			program_hash: program_hash.into(),
+			passed: true,
+			settled_at: 25 - StoragePeriod::get(),
+			expires_at: 25,
 		}));
+		assert_eq!(Verifier::account_quota(1).open, 1);
+		assert_eq!(Verifier::account_quota(2).open, 0);
+		assert_eq!(Verifier::account_quota(3).open, 1);
+		assert_eq!(Verifier::class_quota(&age).open, 1);
+		assert_eq!(Verifier::class_quota(&kyc).open, 1);
+	});
+}
 
+#[test]
+fn should_notify_settled_tasks() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		for class in vec![b"passed".to_vec(), b"rejected".to_vec()] {
+			assert_ok!(Verifier::create_task(
+				Origin::signed(1), class, program_hash.into(), Backend::Distaff, inputs.clone(), outputs.clone(), proof_id.clone(), None, None
+			));
+		}
+		assert_ok!(submit_receipt(0, &b"passed".to_vec(), true));
+		assert!(SETTLED.with(|l| l.borrow().is_empty()));
+		assert_ok!(submit_receipt(1, &b"passed".to_vec(), true));
+		assert_ok!(submit_receipt(0, &b"rejected".to_vec(), false));
+		assert_ok!(submit_receipt(1, &b"rejected".to_vec(), false));
 
-			// then
-			let transaction_1 = pool_state.write().transactions.pop().unwrap();
-			assert_eq!(pool_state.read().transactions.len(), 1);
+		assert_eq!(SETTLED.with(|l| l.borrow().clone()), vec![
+			(1, b"passed".to_vec(), true),
+			(1, b"rejected".to_vec(), false),
+		]);
+	});
+}
 
+#[test]
+fn should_provide_attestations() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let class = b"attestation".to_vec();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		let program_hash: [u8; 32] = program_hash.into();
+		assert_ok!(Verifier::create_task(
+			Origin::signed(1), class.clone(), program_hash, Backend::Distaff, inputs, outputs.clone(), proof_id, None, None
+		));
+		assert!(!<Verifier as VerificationProvider<_, _>>::is_verified(&1, &class, &program_hash));
+
+		assert_ok!(submit_receipt(0, &class, true));
+		assert_ok!(submit_receipt(1, &class, true));
+
+		let now = System::block_number();
+		assert_eq!(Verifier::attestation_of(&1, &class), Some(Attestation {
+			program_hash,
+			passed: true,
+			settled_at: now,
+			expires_at: now + StoragePeriod::get(),
+		}));
+		assert!(Verifier::is_verified(&1, &class, &program_hash));
+		assert!(!Verifier::is_verified(&1, &class, &[0u8; 32]));
+		assert_eq!(Verifier::outputs_of(&1, &class), Some(vec![outputs]));
+
+		// the attestation goes away with the settled task
+		Verifier::on_finalize(now + StoragePeriod::get());
+		assert_eq!(Verifier::attestation_of(&1, &class), None);
+		assert!(!Verifier::is_verified(&1, &class, &program_hash));
+	});
+}
+
+#[test]
+fn should_expire_and_revoke_attestations() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let class = b"credential".to_vec();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		let program_hash: [u8; 32] = program_hash.into();
+
+		// the first account to register a class owns it
+		assert_ok!(Verifier::register_class(Origin::signed(2), class.clone(), program_hash));
+		assert_noop!(
+			Verifier::register_class(Origin::signed(1), class.clone(), [0u8; 32]),
+			Error::<Test>::ClassAlreadyExists,
+		);
+		assert_eq!(Verifier::classes(&class), Some(ClassInfo { owner: 2, program_hash }));
+
+		// only the owner of the class sets its validity period
+		assert_noop!(Verifier::set_class_validity(Origin::signed(1), class.clone(), Some(5)), Error::<Test>::NotClassOwner);
+		assert_noop!(Verifier::set_class_validity(Origin::signed(2), b"none".to_vec(), Some(5)), Error::<Test>::ClassNotExists);
+		assert_ok!(Verifier::set_class_validity(Origin::signed(2), class.clone(), Some(5)));
+		assert_eq!(Verifier::class_validity(&class), Some(5));
+
+		let create = || Verifier::create_task(
+			Origin::signed(1), class.clone(), program_hash, Backend::Distaff, inputs.clone(), outputs.clone(),
+			proof_id.clone(), None, None,
+		);
+		assert_ok!(create());
+		assert_ok!(submit_receipt(0, &class, true));
+		assert_ok!(submit_receipt(1, &class, true));
+		assert_noop!(create(), Error::<Test>::TaskAlreadyExists);
+		let now = System::block_number();
+		assert_eq!(Verifier::attestation_of(&1, &class).map(|attestation| attestation.expires_at), Some(now + 5));
+		assert!(Verifier::is_verified(&1, &class, &program_hash));
+
+		// revoked by the owner of the class
+		assert_noop!(Verifier::revoke_attestation(Origin::signed(3), 1, class.clone()), Error::<Test>::RevocationNotAllowed);
+		assert_ok!(Verifier::revoke_attestation(Origin::signed(2), 1, class.clone()));
+		assert_noop!(Verifier::revoke_attestation(Origin::signed(1), 1, class.clone()), Error::<Test>::AttestationRevoked);
+		assert_eq!(Verifier::revoked_at(&1, &class), Some(now));
+		assert!(!Verifier::is_verified(&1, &class, &program_hash));
+		assert!(System::events().iter().any(|record|
+			record.event == mock::Event::Verifier(crate::Event::AttestationRevoked(1, class.clone(), 2))
+		));
+
+		// expired at the end of the validity period
+		Verifier::on_finalize(now + 5);
+		assert_eq!(Verifier::attestation_of(&1, &class), None);
+		assert_eq!(Verifier::revoked_at(&1, &class), None);
+		assert!(System::events().iter().any(|record|
+			record.event == mock::Event::Verifier(crate::Event::AttestationExpired(1, class.clone()))
+		));
+		assert_noop!(Verifier::revoke_attestation(Origin::signed(1), 1, class.clone()), Error::<Test>::AttestationNotExists);
+
+		// the subject can renew its attestation
+		assert_eq!(Verifier::task_params(1, &class), TaskInfo::default());
+		assert_ok!(create());
+	});
+}
+
+#[test]
+fn should_prove_again_after_revocation() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let class = b"revoked".to_vec();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		let program_hash: [u8; 32] = program_hash.into();
+		let create = || Verifier::create_task(
+			Origin::signed(1), class.clone(), program_hash, Backend::Distaff, inputs.clone(), outputs.clone(),
+			proof_id.clone(), None, None,
+		);
+		assert_ok!(create());
+		assert_ok!(submit_receipt(0, &class, true));
+		assert_ok!(submit_receipt(1, &class, true));
+		let expires_at = System::block_number() + StoragePeriod::get();
+		assert_noop!(create(), Error::<Test>::TaskAlreadyExists);
+
+		// the subject does not wait for the revoked attestation to expire
+		assert_ok!(Verifier::revoke_attestation(Origin::signed(1), 1, class.clone()));
+		assert_ok!(create());
+		assert_eq!(Verifier::attestation_of(&1, &class), None);
+		assert_eq!(Verifier::revoked_at(&1, &class), None);
+		assert!(!SettledTasks::<Test>::contains_key(expires_at, (1, class.clone())));
+		assert!(Verifier::ongoing_tasks(1, &class).is_some());
+
+		System::set_block_number(System::block_number() + 1);
+		assert_ok!(submit_receipt(0, &class, true));
+		assert_ok!(submit_receipt(1, &class, true));
+		assert!(Verifier::is_verified(&1, &class, &program_hash));
+		// the revoked task does not expire the new attestation
+		Verifier::on_finalize(expires_at);
+		assert!(Verifier::is_verified(&1, &class, &program_hash));
+	});
+}
+
+#[test]
+fn should_create_tasks_on_behalf_of_others() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let class = b"delegated".to_vec();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		let authorization = |nonce, valid_until| TaskAuthorization {
+			class: class.clone(),
+			program_hash: program_hash.into(),
+			backend: Backend::Distaff,
+			inputs: inputs.clone(),
+			outputs: outputs.clone(),
+			proof_id: proof_id.clone(),
+			nonce,
+			valid_until,
+		};
+		let sign = |who: u64, authorization: &TaskAuthorization<u64>| {
+			TestSignature(who, Verifier::authorization_payload(authorization))
+		};
 
-		// check the transaction
-		let ex: Extrinsic = Decode::decode(&mut &*transaction_1).unwrap();
-		let receipt = match ex.call {
-			crate::mock::Call::Verifier(crate::Call::submit_verification(r, ..)) => r,
-			e => panic!("Unexpected call: {:?}", e),
+		// account 4 holds no funds and is the subject, account 2 relays and pays
+		let valid = authorization(0, 10);
+		assert_noop!(
+			Verifier::create_task_for(Origin::signed(2), 4, valid.clone(), sign(3, &valid), None),
+			Error::<Test>::BadAuthorization,
+		);
+		// the bare authorization is not bound to this chain
+		assert_noop!(
+			Verifier::create_task_for(Origin::signed(2), 4, valid.clone(), TestSignature(4, valid.encode()), None),
+			Error::<Test>::BadAuthorization,
+		);
+		let expired = authorization(0, 0);
+		assert_noop!(
+			Verifier::create_task_for(Origin::signed(2), 4, expired.clone(), sign(4, &expired), None),
+			Error::<Test>::AuthorizationExpired,
+		);
+		let ahead = authorization(1, 10);
+		assert_noop!(
+			Verifier::create_task_for(Origin::signed(2), 4, ahead.clone(), sign(4, &ahead), None),
+			Error::<Test>::InvalidNonce,
+		);
+		assert_ok!(Verifier::create_task_for(Origin::signed(2), 4, valid.clone(), sign(4, &valid), None));
+		assert_eq!(Verifier::authorization_nonces(4), 1);
+		assert_eq!(Balances::free_balance(2), 100 - TaskFee::get());
+		assert!(Verifier::ongoing_tasks(4, &class).is_some());
+
+		// an authorization can not be replayed
+		assert_noop!(
+			Verifier::create_task_for(Origin::signed(3), 4, valid.clone(), sign(4, &valid), None),
+			Error::<Test>::InvalidNonce,
+		);
+
+		// a relayer onboarding many subjects is exempted from the quotas of the class
+		assert_ok!(Verifier::create_task_for(Origin::signed(2), 5, valid.clone(), sign(5, &valid), None));
+		assert_noop!(
+			Verifier::create_task_for(Origin::signed(2), 6, valid.clone(), sign(6, &valid), None),
+			Error::<Test>::ClassQuotaExceeded,
+		);
+		assert_ok!(Verifier::set_quota_exempt(Origin::root(), 2, true));
+		assert_ok!(Verifier::create_task_for(Origin::signed(2), 6, valid.clone(), sign(6, &valid), None));
+		assert_eq!(Verifier::class_quota(&class).open, 3);
+		assert_eq!(Verifier::account_quota(6).open, 1);
+	});
+}
+
+#[test]
+fn should_settle_bundles_as_a_whole() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let class = b"bundle".to_vec();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		let component = |program_hash: [u8; 32], outputs: Vec<u128>| TaskInfo {
+			proof_id: proof_id.clone(), inputs: inputs.clone(), outputs, program_hash, backend: Backend::Distaff,
 		};
+		let components = vec![component(program_hash.into(), outputs.clone()), component([1u8; 32], vec![42])];
 
-		// pop the last one
-		assert_eq!(receipt.clone(), VerificationReceipt {
-			program_hash: program_hash,
-    		passed: true,
-    		submit_at: System::block_number(),
-    		auth_index: 1,
-    		validators_len: 3
-		});
-		// test ValidateUnsigned
-		let signature = UintAuthorityId(2).sign(&receipt.clone().encode()).unwrap();
-		let call = crate::Call::submit_verification(receipt.clone(), signature.clone());
-		assert_ok!(
-			<Verifier as sp_runtime::traits::ValidateUnsigned>::validate_unsigned(
-				TransactionSource::External,
-				&call
-			)
+		assert_noop!(Verifier::create_bundle(Origin::signed(1), class.clone(), vec![], None), Error::<Test>::EmptyBundle);
+		assert_noop!(
+			Verifier::create_bundle(Origin::signed(1), class.clone(), vec![components[0].clone(); 3], None),
+			Error::<Test>::BundleTooLarge,
 		);
+		assert_ok!(Verifier::create_bundle(Origin::signed(1), class.clone(), components.clone(), None));
+		let bundle_hash = Verifier::bundle_hash(&components);
+		assert_eq!(Verifier::task_params(1, &class).program_hash, bundle_hash);
+		assert_eq!(Verifier::task_bundles(1, &class), components);
+		// a single fee for the whole bundle
+		assert_eq!(Balances::free_balance(1), 100 - TaskFee::get());
 
-		let _ = Verifier::submit_verification(Origin::none(), receipt, signature);
+		// the votes cover the whole bundle
+		for auth_index in 0..2 {
+			assert_ok!(submit_receipt_for(auth_index, 1, &class, bundle_hash, true, false));
+		}
+		assert!(Verifier::is_verified(&1, &class, &bundle_hash));
+		assert!(!Verifier::is_verified(&1, &class, &program_hash.into()));
+		// the outputs of every component are kept apart
+		assert_eq!(Verifier::outputs_of(&1, &class), Some(vec![outputs, vec![42]]));
+	});
+}
 
-		let block_number = System::block_number() + 20;
-		// check the online status, should be removed
-		let status = Verifier::ongoing_tasks(&program_hash);
-		assert_eq!(status, None);
-		let settled_task = Verifier::settled_tasks(&block_number, &program_hash);
-		assert_eq!(settled_task, true);
+#[test]
+fn should_reject_insecure_proofs() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let class = b"secure".to_vec();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		let program_hash: [u8; 32] = program_hash.into();
+		Classes::<Test>::insert(&class, ClassInfo { owner: 2, program_hash });
 
-		Verifier::on_finalize(block_number);
-		
-		assert_eq!(Verifier::settled_tasks(&block_number, &program_hash), false);
+		// only the owner of the class raises its security level
+		assert_noop!(
+			Verifier::set_class_security_level(Origin::signed(1), class.clone(), Some(1024)),
+			Error::<Test>::NotClassOwner,
+		);
+		assert_ok!(Verifier::set_class_security_level(Origin::signed(2), class.clone(), Some(1024)));
+		assert_eq!(Verifier::min_security_level(&class), 1024);
+		assert_eq!(Verifier::min_security_level(&b"other".to_vec()), MinSecurityLevel::get());
+
+		// the proof is valid, but claims less than 1024 bits
+		let proof = new_proof().unwrap();
+		assert_ok!(Verifier::is_secure_enough(Backend::Distaff, &proof, MinSecurityLevel::get()), true);
+		assert_ok!(Verifier::is_secure_enough(Backend::Distaff, &proof, 1024), false);
+
+		assert_ok!(Verifier::create_task(
+			Origin::signed(1), class.clone(), program_hash, Backend::Distaff, inputs, outputs, proof_id, None, None
+		));
+		for auth_index in 0..2 {
+			assert_ok!(submit_receipt_for(auth_index, 1, &class, program_hash, true, true));
+		}
+		assert_eq!(Verifier::attestation_of(&1, &class).map(|attestation| attestation.passed), Some(false));
+		assert_eq!(Verifier::insecure_votes(1, &class), 0);
+		assert!(System::events().iter().any(|record|
+			record.event == mock::Event::Verifier(crate::Event::TaskRejectedInsecure(1, class.clone()))
+		));
+	});
+}
 
+#[test]
+fn should_verify_on_chain() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let (program_hash, inputs, outputs, _) = task_params();
+		let proof = new_proof().unwrap();
+		let verify = |who: u64, class: &[u8], backend, proof: Vec<u8>| Verifier::verify_on_chain(
+			Origin::signed(who), class.to_vec(), program_hash.into(), backend, inputs.clone(), outputs.clone(), proof,
+		);
+
+		assert_noop!(
+			verify(1, b"large", Backend::Distaff, vec![0u8; MaxOnChainProofSize::get() as usize + 1]),
+			Error::<Test>::ProofTooLarge,
+		);
+		assert_noop!(verify(1, b"miden", Backend::Miden, proof.clone()), Error::<Test>::UnsupportedBackend);
+
+		// settled in the same block, without any vote
+		assert_ok!(verify(1, b"valid", Backend::Distaff, proof.clone()));
+		assert!(Verifier::is_verified(&1, &b"valid".to_vec(), &program_hash.into()));
+		assert_eq!(Verifier::ongoing_tasks(1, b"valid".to_vec()), None);
+		assert_eq!(Verifier::account_quota(1).open, 0);
+		assert_noop!(verify(1, b"valid", Backend::Distaff, proof.clone()), Error::<Test>::TaskAlreadyExists);
+
+		let mut corrupted = proof;
+		corrupted.truncate(100);
+		assert_ok!(verify(2, b"invalid", Backend::Distaff, corrupted));
+		assert_eq!(Verifier::attestation_of(&2, &b"invalid".to_vec()).map(|attestation| attestation.passed), Some(false));
+		assert!(System::events().iter().any(|record|
+			record.event == mock::Event::Verifier(crate::Event::TaskVerifiedOnChain(2, b"invalid".to_vec(), false))
+		));
+	});
+}
+
+#[test]
+fn should_cache_verdicts() {
+	let (offchain, _) = TestOffchainExt::new();
+	let mut ext = new_test_ext();
+	ext.register_extension(OffchainDbExt::new(offchain.clone()));
+	ext.register_extension(OffchainWorkerExt::new(offchain));
+
+	ext.execute_with(|| {
+		assert_eq!(Verifier::cached_verdict(&[1u8; 32]), None);
+		Verifier::cache_verdict([1u8; 32], Verdict::Passed);
+		Verifier::cache_verdict([2u8; 32], Verdict::Insecure);
+		assert_eq!(Verifier::cached_verdict(&[1u8; 32]), Some(Verdict::Passed));
+		assert_eq!(Verifier::cached_verdict(&[2u8; 32]), Some(Verdict::Insecure));
+
+		let cache = StorageValueRef::persistent(CACHE_DB_KEY).get::<VerificationCache>().flatten().unwrap();
+		assert_eq!((cache.hits, cache.misses), (2, 1));
+	});
+
+	// the least recently used verdict is evicted first
+	let key = |index: usize| sp_io::hashing::blake2_256(&index.to_le_bytes());
+	let mut cache = VerificationCache::default();
+	for index in 0..CACHE_SIZE {
+		cache.insert(key(index), Verdict::Failed);
+	}
+	assert_eq!(cache.lookup(&key(0)), Some(Verdict::Failed));
+	cache.insert(key(CACHE_SIZE), Verdict::Passed);
+	assert_eq!(cache.entries.len(), CACHE_SIZE);
+	assert_eq!(cache.lookup(&key(1)), None);
+	assert_eq!(cache.lookup(&key(0)), Some(Verdict::Failed));
+}
+
+#[test]
+fn should_execute_urgent_tasks_first() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		let create = |who: u64, class: &[u8], priority_fee: Option<u64>| {
+			assert_ok!(Verifier::create_task(
+				Origin::signed(who), class.to_vec(), program_hash.into(), Backend::Distaff, inputs.clone(), outputs.clone(),
+				proof_id.clone(), None, priority_fee,
+			));
+		};
+		create(3, b"cheap", None);
+		create(1, b"old", Some(5));
+		System::set_block_number(System::block_number() + 1);
+		create(2, b"new", Some(5));
+		create(1, b"cheaper", Some(1));
+		// the priority fee goes into the reward pool along with the task fee
+		assert_eq!(Verifier::task_fees(1, b"old".to_vec()), TaskFee::get() + 5);
+
+		let mut local_tasks = BTreeSet::new();
+		let mut order = vec![];
+		while let Ok(task) = Verifier::task_to_execute(&local_tasks) {
+			order.push(task.clone());
+			local_tasks.insert(task);
+		}
+		assert_eq!(order, vec![
+			(1, b"old".to_vec()),
+			(2, b"new".to_vec()),
+			(1, b"cheaper".to_vec()),
+			(3, b"cheap".to_vec()),
+		]);
+	});
+}
+
+#[test]
+fn should_enforce_quotas() {
+	new_test_ext().execute_with(|| {
+		set_validators();
+		let (program_hash, inputs, outputs, proof_id) = task_params();
+		let create = |who: u64, class: &[u8]| Verifier::create_task(
+			Origin::signed(who), class.to_vec(), program_hash.into(), Backend::Distaff, inputs.clone(), outputs.clone(),
+			proof_id.clone(), None, None,
+		);
+		let settle = |who: u64, class: &[u8]| {
+			for auth_index in 0..2 {
+				assert_ok!(submit_receipt_for(auth_index, who, &class.to_vec(), program_hash.into(), true, false));
+			}
+		};
+
+		// open tasks of an account
+		assert_ok!(create(1, b"a"));
+		assert_ok!(create(1, b"b"));
+		assert_noop!(create(1, b"c"), Error::<Test>::AccountQuotaExceeded);
+		settle(1, b"a");
+		assert_eq!(Verifier::account_quota(1).open, 1);
+		assert_ok!(create(1, b"c"));
+
+		// tasks created by an account in a window
+		settle(1, b"b");
+		assert_noop!(create(1, b"d"), Error::<Test>::AccountQuotaExceeded);
+		System::set_block_number(QuotaWindow::get());
+		assert_ok!(create(1, b"d"));
 
+		// open tasks of a class
+		assert_ok!(create(2, b"d"));
+		assert_noop!(create(3, b"d"), Error::<Test>::ClassQuotaExceeded);
+
+		// trusted issuers are not limited
+		assert_noop!(Verifier::set_quota_exempt(Origin::signed(3), 3, true), sp_runtime::DispatchError::BadOrigin);
+		assert_ok!(Verifier::set_quota_exempt(Origin::root(), 3, true));
+		assert_ok!(create(3, b"d"));
+		assert_eq!(Verifier::class_quota(b"d".to_vec()).open, 3);
 	});
 }
 
+fn set_validators() {
+	System::set_block_number(1);
+	advance_session();
+	advance_session();
+	assert_eq!(Session::validators(), vec![1, 2, 3]);
+}
+
+// Sign and submit the vote of the validator at `auth_index` on the task of account 1
+fn submit_receipt(auth_index: u32, class: &Class, passed: bool) -> dispatch::DispatchResult {
+	let (program_hash, ..) = task_params();
+	submit_receipt_for(auth_index, 1, class, program_hash.into(), passed, false)
+}
+
+// Sign and submit the vote of the validator at `auth_index` on the task `(subject, class)`
+fn submit_receipt_for(
+	auth_index: u32,
+	subject: u64,
+	class: &Class,
+	program_hash: [u8; 32],
+	passed: bool,
+	insecure: bool,
+) -> dispatch::DispatchResult {
+	let receipt = VerificationReceipt {
+		task_tuple_id: (subject, class.clone()),
+		program_hash,
+		passed,
+		insecure,
+		submit_at: System::block_number(),
+		auth_index,
+		session_index: Session::current_index(),
+	};
+	let signature = Verifier::keys()[auth_index as usize].sign(&receipt.encode()).unwrap();
+	Verifier::submit_verification(Origin::none(), receipt, signature)
+}
+
 fn set_key_and_tasks() {
 
 	// set keys
@@ -240,8 +1007,10 @@ fn set_key_and_tasks() {
 	assert_eq!(Session::validators(), vec![1, 2, 3]);
 	assert_eq!(Verifier::keys(), vec![UintAuthorityId(1), UintAuthorityId(2), UintAuthorityId(3)]);
 	// craete task
-	let (progam_hash, inputs, outputs, proof_id) = task_params();
-	Verifier::create_task(Origin::signed(1), progam_hash.clone(), inputs.clone(), outputs.clone(), proof_id.clone());
+	let (program_hash, inputs, outputs, proof_id) = task_params();
+	assert_ok!(Verifier::create_task(
+		Origin::signed(1), b"class".to_vec(), program_hash.into(), Backend::Distaff, inputs, outputs, proof_id, None, None
+	));
 }
 
 // return program_hash, inputs, outputs, proof_id
@@ -255,7 +1024,7 @@ fn task_params() -> (H256, Vec<u128>, Vec<u128>, Vec<u8>) {
 }
 
 fn three_http_request(state: &mut testing::OffchainState)  {
-	let (program_hash, inputs, outputs, proof_id) = task_params();
+	let (.., proof_id) = task_params();
 	let uri = "https://ipfs.infura.io:5001/api/v0/cat?arg=".to_owned() + sp_std::str::from_utf8(&proof_id[..]).unwrap();
 	state.expect_request(testing::PendingRequest {
 		method: "GET".into(),
@@ -281,40 +1050,3 @@ fn three_http_request(state: &mut testing::OffchainState)  {
 		..Default::default()
 	});
 }
-
-fn prepare_submission(
-	block_number: u64,
-	auth_index: u32,
-	id: UintAuthorityId,
-	hash: H256,
-	validators: Vec<u64>
-) -> dispatch::DispatchResult {
-	use frame_support::unsigned::ValidateUnsigned;
-
-	let verification_receipt = VerificationReceipt {
-		program_hash: hash,
-		// when a task is passed or not
-		passed: true,
-		submit_at: block_number,
-		// submitted by who
-		auth_index: auth_index,
-		validators_len: validators.len() as u32,
-	};
-
-	let signature = id.sign(&verification_receipt.encode()).unwrap();
-	
-	Verifier::pre_dispatch(&crate::Call::submit_verification(verification_receipt.clone(), signature.clone()))
-		.map_err(|e| match e {
-			TransactionValidityError::Invalid(InvalidTransaction::Custom(INVALID_VALIDATORS_LEN)) =>
-				"invalid validators len",
-			e @ _ => <&'static str>::from(e),
-	})?;
-
-	Verifier::submit_verification(Origin::none(), verification_receipt, signature)
-}
-
-
-
-
- 
-
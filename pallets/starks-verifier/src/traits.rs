@@ -0,0 +1,41 @@
+//! Traits for other modules to react to the outcome of verification tasks, and to query it.
+
+use sp_std::prelude::*;
+use crate::{Attestation, Class};
+
+/// Something which should be notified when a task is settled as passed,
+/// e.g. to grant access or to unlock funds.
+///
+/// It is implemented for tuples, so that several modules can subscribe.
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+pub trait OnTaskSettled<AccountId> {
+    /// The task of `who` for `class`, running the program of `program_hash`, is passed.
+    fn on_task_settled(who: &AccountId, class: &Class, program_hash: &[u8; 32]);
+}
+
+/// Something which should be notified when a task is settled as not passed.
+///
+/// It is implemented for tuples, so that several modules can subscribe.
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+pub trait OnTaskRejected<AccountId> {
+    /// The task of `who` for `class`, running the program of `program_hash`, is rejected.
+    fn on_task_rejected(who: &AccountId, class: &Class, program_hash: &[u8; 32]);
+}
+
+/// Something which can tell whether an account holds a valid attestation, without
+/// depending on how the attestations are stored.
+pub trait VerificationProvider<AccountId, BlockNumber> {
+    /// Whether `who` holds a passed attestation for `class`, produced by the program of `program_hash`,
+    /// which is neither revoked nor expired.
+    fn is_verified(who: &AccountId, class: &Class, program_hash: &[u8; 32]) -> bool;
+
+    /// The attestation of `who` for `class`, passed or not.
+    fn attestation_of(who: &AccountId, class: &Class) -> Option<Attestation<BlockNumber>>;
+
+    /// The public outputs of the programs behind the attestation of `who` for `class`, if it is passed:
+    /// one entry per program, in the order of the components of a bundle.
+    fn outputs_of(who: &AccountId, class: &Class) -> Option<Vec<Vec<u128>>>;
+
+    /// The block number at which the attestation of `who` for `class` is revoked, if it is.
+    fn revoked_at(who: &AccountId, class: &Class) -> Option<BlockNumber>;
+}
@@ -0,0 +1,57 @@
+//! Weights for the Starks-verifier Module
+//!
+//! These weights are PLACEHOLDERS estimated by hand, not the output of the benchmarks: they
+//! overestimate the cost of the calls until the benchmarks are run on reference hardware.
+//! Only the calls whose cost depends on their arguments, or which verify proofs, are weighted
+//! here, the others keep their flat weight. Replace this file after running the benchmarks with:
+//!
+//! ```text
+//! ./target/release/zcloak-node benchmark --chain=dev --steps=50 --repeat=20 \
+//!     --pallet=pallet_starks_verifier --extrinsic='*' --execution=wasm --wasm-execution=compiled \
+//!     --output=./pallets/starks-verifier/src/weights.rs
+//! ```
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+use sp_std::marker::PhantomData;
+
+/// Weight functions needed for pallet_starks_verifier.
+pub trait WeightInfo {
+	fn create_task(s: u32, ) -> Weight;
+	fn verify_on_chain(s: u32, ) -> Weight;
+}
+
+/// Weights for pallet_starks_verifier using the Substrate node and recommended hardware.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	fn create_task(s: u32, ) -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add((2_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(T::DbWeight::get().reads(8 as Weight))
+			.saturating_add(T::DbWeight::get().writes(8 as Weight))
+	}
+	fn verify_on_chain(s: u32, ) -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add((90_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(T::DbWeight::get().reads(11 as Weight))
+			.saturating_add(T::DbWeight::get().writes(8 as Weight))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	fn create_task(s: u32, ) -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add((2_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(RocksDbWeight::get().reads(8 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
+	}
+	fn verify_on_chain(s: u32, ) -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add((90_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(RocksDbWeight::get().reads(11 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
+	}
+}
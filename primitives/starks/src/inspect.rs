@@ -0,0 +1,138 @@
+//! Decode a proof and describe what is inside it, to tell why it is refused without having
+//! to verify it against the inputs and outputs of a task.
+
+use distaff::StarkProof;
+use crate::{Backend, VerifyError, MAX_PROOF_SIZE};
+
+/// The structure of a decoded proof.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ProofSummary {
+	/// The backend the proof is decoded with
+	pub backend: Backend,
+	/// The size in bytes of the whole proof
+	pub size: usize,
+	/// The number of steps of the execution trace
+	pub trace_length: usize,
+	/// The depth of the stack at the end of the execution
+	pub stack_depth: usize,
+	/// The blowup factor of the execution trace
+	pub extension_factor: usize,
+	/// The number of queries of the proof
+	pub num_queries: usize,
+	/// The number of bits of proof-of-work
+	pub grinding_factor: u32,
+	/// The security level in bits claimed by the options of the proof
+	pub security_level: u32,
+	/// The size in bytes of the queried trace evaluations and their Merkle paths
+	pub trace_queries_size: usize,
+	/// The size in bytes of the queried constraint evaluations and their Merkle paths
+	pub constraint_queries_size: usize,
+	/// The size in bytes of the proof of the degree of the composition polynomial
+	pub degree_proof_size: usize,
+	/// The hash of the program, if the proof embeds it. Distaff proofs do not.
+	pub program_hash: Option<[u8; 32]>,
+	/// Why the proof is not well formed, if it is not
+	pub malformations: Vec<String>,
+}
+
+/// Decode `proof` with `backend` and describe its structure, without verifying it.
+pub fn inspect(backend: Backend, proof: &[u8]) -> Result<ProofSummary, VerifyError> {
+	if proof.len() > MAX_PROOF_SIZE {
+		return Err(VerifyError::ResourceLimit);
+	}
+	match backend {
+		Backend::Distaff => {
+			let stark_proof = bincode::deserialize::<StarkProof>(proof)
+				.map_err(|_e| VerifyError::MalformedProof)?;
+			std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| summarize(proof.len(), &stark_proof)))
+				.map_err(|_e| VerifyError::MalformedProof)
+		},
+		// Miden proofs are decoded by their own crate, which gives no access to their structure.
+		Backend::Miden => Err(VerifyError::UnsupportedVersion),
+	}
+}
+
+fn serialized_size<S: serde::Serialize>(value: &S) -> usize {
+	bincode::serialized_size(value).unwrap_or_default() as usize
+}
+
+fn summarize(size: usize, proof: &StarkProof) -> ProofSummary {
+	let options = proof.options();
+	let trace_length = proof.trace_length();
+	let mut malformations = Vec::new();
+	if !trace_length.is_power_of_two() {
+		malformations.push(format!("the trace length {} is not a power of two", trace_length));
+	}
+	if !options.extension_factor().is_power_of_two() {
+		malformations.push(format!("the extension factor {} is not a power of two", options.extension_factor()));
+	}
+	if proof.domain_size() != trace_length * options.extension_factor() {
+		malformations.push(format!(
+			"the evaluation domain has {} points instead of {}",
+			proof.domain_size(),
+			trace_length * options.extension_factor(),
+		));
+	}
+	if proof.trace_evaluations().is_empty() {
+		malformations.push("no trace evaluation is queried".to_string());
+	}
+	if proof.trace_evaluations().len() > options.num_queries() {
+		malformations.push(format!(
+			"{} trace evaluations are queried, but the options allow {}",
+			proof.trace_evaluations().len(),
+			options.num_queries(),
+		));
+	}
+
+	ProofSummary {
+		backend: Backend::Distaff,
+		size,
+		trace_length,
+		stack_depth: proof.stack_depth(),
+		extension_factor: options.extension_factor(),
+		num_queries: options.num_queries(),
+		grinding_factor: options.grinding_factor(),
+		security_level: proof.security_level(true),
+		trace_queries_size: serialized_size(proof.trace_proof()) + serialized_size(&proof.trace_evaluations()),
+		constraint_queries_size: serialized_size(proof.constraint_proof()),
+		degree_proof_size: serialized_size(proof.degree_proof()),
+		program_hash: None,
+		malformations,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// A distaff proof with the inputs `[1, 0]` and the outputs `[8]`
+	const PROOF: &[u8] = include_bytes!("../../../pallets/starks-verifier/proof.txt");
+
+	#[test]
+	fn should_summarize_valid_proofs() {
+		let summary = inspect(Backend::Distaff, PROOF).unwrap();
+		assert_eq!(summary.size, PROOF.len());
+		assert!(summary.trace_length.is_power_of_two());
+		assert_eq!(crate::security_level_of(Backend::Distaff, PROOF), Ok(summary.security_level));
+		assert!(summary.malformations.is_empty(), "{:?}", summary.malformations);
+	}
+
+	#[test]
+	fn should_report_malformed_proofs() {
+		// The options are serialized last: the extension factor as a power of two, the number
+		// of queries, the grinding factor and the hash function.
+		let options = PROOF.len() - 4;
+		let summary = inspect(Backend::Distaff, PROOF).unwrap();
+		assert_eq!(summary.extension_factor, 1 << PROOF[options]);
+		assert_eq!(summary.num_queries, PROOF[options + 1] as usize);
+
+		// more trace evaluations than the options allow queries
+		let mut corrupted = PROOF.to_vec();
+		corrupted[options + 1] = 1;
+		let summary = inspect(Backend::Distaff, &corrupted).unwrap();
+		assert!(!summary.malformations.is_empty());
+
+		assert_eq!(inspect(Backend::Distaff, &PROOF[..PROOF.len() / 2]), Err(VerifyError::MalformedProof));
+		assert_eq!(inspect(Backend::Miden, PROOF), Err(VerifyError::UnsupportedVersion));
+	}
+}
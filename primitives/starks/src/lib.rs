@@ -10,13 +10,133 @@
    doc = "Substrate's runtime standard library as compiled without Rust's standard library.")]
 
 
-use sp_runtime_interface::{runtime_interface, Pointer};
+use codec::{Encode, Decode};
+use sp_runtime_interface::{runtime_interface, Pointer, pass_by::PassByCodec};
+#[cfg(feature = "std")]
+use serde::{Serialize, Deserialize};
 
 #[cfg(feature = "std")]
 use distaff::StarkProof;
+#[cfg(feature = "std")]
+use sp_core::offchain::OffchainWorkerExt;
+#[cfg(feature = "std")]
+use sp_externalities::ExternalitiesExt;
+
+#[cfg(feature = "std")]
+pub mod inspect;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod program;
+#[cfg(feature = "std")]
+pub mod proof_store;
+
+/// The proof system a program is proved with, and so the verifier its proofs are checked by.
+///
+/// New variants are only ever appended, so that the tasks stored on chain keep their backend.
+#[derive(Encode, Decode, PassByCodec, Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum Backend {
+	/// Proofs of the distaff VM, serialized with bincode
+	Distaff,
+	/// Proofs of the Miden VM, the successor of distaff built on Winterfell. Reserved: they are
+	/// refused by every node until all of them can verify them.
+	Miden,
+}
+
+impl Default for Backend {
+	fn default() -> Self {
+		Backend::Distaff
+	}
+}
+
+/// Why a proof could not be verified.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VerifyError {
+	/// The proof can not be decoded
+	MalformedProof,
+	/// The proof is decoded, but does not prove the program with these inputs and outputs
+	InvalidProof,
+	/// The backend of the proof is not supported by this node
+	UnsupportedVersion,
+	/// The proof is larger than `MAX_PROOF_SIZE`
+	ResourceLimit,
+}
+
+/// Something which happened in an offchain worker verifying proofs, reported to the node
+/// to be exposed in its metrics.
+#[derive(Encode, Decode, PassByCodec, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WorkerEvent {
+	/// A proof is fetched, or failed to be
+	ProofFetched {
+		/// The time the fetch took, in milliseconds
+		millis: u64,
+		/// Whether the proof is fetched
+		success: bool,
+	},
+	/// The task to verify is locked by another worker
+	LockContended,
+	/// A verification is submitted, or failed to be
+	Submitted {
+		/// Whether the transaction is accepted by the pool
+		success: bool,
+	},
+}
+
+/// The size in bytes of the largest proof which is verified.
+pub const MAX_PROOF_SIZE: usize = 1024 * 1024;
+
+/// Verify `proof` with the verifier of `backend`.
+///
+/// The verifiers are not meant to be fed with hostile input, so whatever makes them panic
+/// is reported as a `MalformedProof` instead of taking the node down.
+#[cfg(feature = "std")]
+fn verify_with(
+	backend: Backend,
+	program_hash: &[u8; 32],
+	public_inputs: &[u128],
+	outputs: &[u128],
+	proof: &[u8]) -> Result<bool, VerifyError>
+{
+	let started = std::time::Instant::now();
+	let result = if proof.len() > MAX_PROOF_SIZE {
+		Err(VerifyError::ResourceLimit)
+	} else {
+		std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| match backend {
+			Backend::Distaff => {
+				let stark_proof = bincode::deserialize::<StarkProof>(proof)
+					.map_err(|_e| VerifyError::MalformedProof)?;
+				distaff::verify(program_hash, public_inputs, outputs, &stark_proof)
+					.map_err(|_e| VerifyError::InvalidProof)
+			},
+			// No node verifies these proofs yet, so that every node gives the same answer for them.
+			Backend::Miden => Err(VerifyError::UnsupportedVersion),
+		})).unwrap_or(Err(VerifyError::MalformedProof))
+	};
+	metrics::record_verification(&result, started.elapsed());
+	result
+}
+
+/// The security level in bits claimed by the options `proof` is generated with, under the
+/// usual conjectures on the soundness of FRI.
+#[cfg(feature = "std")]
+fn security_level_of(backend: Backend, proof: &[u8]) -> Result<u32, VerifyError> {
+	if proof.len() > MAX_PROOF_SIZE {
+		return Err(VerifyError::ResourceLimit);
+	}
+	std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| match backend {
+		Backend::Distaff => {
+			let stark_proof = bincode::deserialize::<StarkProof>(proof)
+				.map_err(|_e| VerifyError::MalformedProof)?;
+			Ok(stark_proof.security_level(true))
+		},
+		Backend::Miden => Err(VerifyError::UnsupportedVersion),
+	})).unwrap_or(Err(VerifyError::MalformedProof))
+}
 
 #[runtime_interface]
 pub trait Starks {
+	/// Verify a proof of the distaff VM.
 	fn verify(
 		&mut self,
 		program_hash: &[u8; 32],
@@ -24,7 +144,45 @@ pub trait Starks {
 		outputs: &[u128],
 		proof: &[u8]) -> Result<bool, ()>
 	{
-		let stark_proof = bincode::deserialize::<StarkProof>(&proof).unwrap();
-		distaff::verify(program_hash, public_inputs, outputs, &stark_proof).map_err(|_e| ())
+		verify_with(Backend::Distaff, program_hash, public_inputs, outputs, proof).map_err(|_e| ())
+	}
+
+	/// Verify a proof of the program of `program_hash`, with the verifier of `backend`,
+	/// telling why it could not be verified.
+	#[version(2)]
+	fn verify(
+		&mut self,
+		backend: Backend,
+		program_hash: &[u8; 32],
+		public_inputs: &[u128],
+		outputs: &[u128],
+		proof: &[u8]) -> Result<bool, VerifyError>
+	{
+		verify_with(backend, program_hash, public_inputs, outputs, proof)
+	}
+
+	/// The security level in bits claimed by the options `proof` is generated with, so that
+	/// cheap proofs can be told apart before they are verified.
+	fn security_level(&mut self, backend: Backend, proof: &[u8]) -> Result<u32, VerifyError> {
+		security_level_of(backend, proof)
+	}
+
+	/// Report what happened in an offchain worker, to be exposed in the metrics of the node.
+	fn report(&mut self, event: WorkerEvent) {
+		metrics::record_worker_event(event)
+	}
+
+	/// The proof of id `proof_id` in the local proof store of the node, if it keeps one and
+	/// the proof is in it.
+	///
+	/// Only offchain workers read the store, and `None` is returned anywhere else, so that the
+	/// import of a block never depends on the files of a node.
+	fn fetch_local_proof(&mut self, proof_id: &[u8]) -> Option<Vec<u8>> {
+		self.extension::<OffchainWorkerExt>()?;
+		let id = std::str::from_utf8(proof_id).ok()?;
+		proof_store::store()?.get(id).unwrap_or_else(|e| {
+			log::warn!(target: "starks-verifier", "Failed to read proof {} from the local store: {}", id, e);
+			None
+		})
 	}
 }
@@ -0,0 +1,114 @@
+//! Prometheus metrics of the verification of proofs, recorded on the host side of the `Starks`
+//! interface and reported by the offchain workers through `Starks::report`.
+//!
+//! The host functions have no handle on the node, so the metrics live in a global which the
+//! node fills in with `register`. Nothing is recorded until it does.
+
+use std::time::Duration;
+use once_cell::sync::OnceCell;
+use prometheus_endpoint::{
+	register, Counter, CounterVec, Histogram, HistogramOpts, Opts, PrometheusError, Registry, U64,
+};
+use crate::{VerifyError, WorkerEvent};
+
+static METRICS: OnceCell<Metrics> = OnceCell::new();
+
+/// The metrics of the verifier
+struct Metrics {
+	verifications: CounterVec<U64>,
+	verification_time: Histogram,
+	fetches: CounterVec<U64>,
+	fetch_time: Histogram,
+	lock_contention: Counter<U64>,
+	submissions: CounterVec<U64>,
+}
+
+impl Metrics {
+	fn new(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			verifications: register(
+				CounterVec::new(
+					Opts::new("starks_verifications_total", "Number of proofs verified, by outcome"),
+					&["outcome"],
+				)?,
+				registry,
+			)?,
+			verification_time: register(
+				Histogram::with_opts(HistogramOpts::new(
+					"starks_verification_duration_seconds",
+					"Time taken to verify a proof",
+				))?,
+				registry,
+			)?,
+			fetches: register(
+				CounterVec::new(
+					Opts::new("starks_proof_fetches_total", "Number of proofs fetched by the offchain workers, by result"),
+					&["result"],
+				)?,
+				registry,
+			)?,
+			fetch_time: register(
+				Histogram::with_opts(HistogramOpts::new(
+					"starks_proof_fetch_duration_seconds",
+					"Time taken by the offchain workers to fetch a proof",
+				))?,
+				registry,
+			)?,
+			lock_contention: register(
+				Counter::new(
+					"starks_task_lock_contention_total",
+					"Number of times an offchain worker found its task locked by another one",
+				)?,
+				registry,
+			)?,
+			submissions: register(
+				CounterVec::new(
+					Opts::new("starks_submissions_total", "Number of verifications submitted, by result"),
+					&["result"],
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// Register the metrics of the verifier in `registry`. Only the first registry is used.
+pub fn register_metrics(registry: &Registry) -> Result<(), PrometheusError> {
+	if METRICS.get().is_none() {
+		let _ = METRICS.set(Metrics::new(registry)?);
+	}
+	Ok(())
+}
+
+fn result_label(success: bool) -> &'static str {
+	if success { "ok" } else { "error" }
+}
+
+pub(crate) fn record_verification(result: &Result<bool, VerifyError>, elapsed: Duration) {
+	if let Some(metrics) = METRICS.get() {
+		let outcome = match result {
+			Ok(true) => "passed",
+			Ok(false) => "failed",
+			Err(VerifyError::MalformedProof) => "malformed",
+			Err(VerifyError::InvalidProof) => "invalid",
+			Err(VerifyError::UnsupportedVersion) => "unsupported",
+			Err(VerifyError::ResourceLimit) => "resource_limit",
+		};
+		metrics.verifications.with_label_values(&[outcome]).inc();
+		metrics.verification_time.observe(elapsed.as_secs_f64());
+	}
+}
+
+pub(crate) fn record_worker_event(event: WorkerEvent) {
+	if let Some(metrics) = METRICS.get() {
+		match event {
+			WorkerEvent::ProofFetched { millis, success } => {
+				metrics.fetches.with_label_values(&[result_label(success)]).inc();
+				metrics.fetch_time.observe(millis as f64 / 1000.0);
+			},
+			WorkerEvent::LockContended => metrics.lock_contention.inc(),
+			WorkerEvent::Submitted { success } =>
+				metrics.submissions.with_label_values(&[result_label(success)]).inc(),
+		}
+	}
+}
@@ -0,0 +1,70 @@
+//! Helpers to reproduce the hash of a distaff program from its assembly source, so that
+//! creators and registries do not have to trust external tooling for the `program_hash`
+//! of a task or a class, and to prove its execution.
+
+pub use distaff::{Program, ProofOptions};
+
+/// Compile distaff assembly `source` into a program.
+pub fn compile(source: &str) -> Result<Program, String> {
+	distaff::assembly::compile(source).map_err(|e| e.to_string())
+}
+
+/// The hash of the program compiled from distaff assembly `source`.
+pub fn program_hash(source: &str) -> Result<[u8; 32], String> {
+	compile(source).map(|program| *program.hash())
+}
+
+/// Whether `expected` is the hash of the program compiled from distaff assembly `source`.
+pub fn check_program_hash(source: &str, expected: &[u8; 32]) -> Result<bool, String> {
+	program_hash(source).map(|hash| &hash == expected)
+}
+
+/// Execute `program` on the public and secret inputs, and prove it with `options`.
+///
+/// Returns the first `num_outputs` values of the stack, and the proof serialized with bincode
+/// as the verifiers expect it.
+pub fn prove(
+	program: &Program,
+	public_inputs: &[u128],
+	secret_inputs: (&[u128], &[u128]),
+	num_outputs: usize,
+	options: &ProofOptions) -> Result<(Vec<u128>, Vec<u8>), String>
+{
+	let inputs = distaff::ProgramInputs::new(public_inputs, secret_inputs.0, secret_inputs.1);
+	// The prover panics on inputs which do not satisfy the program.
+	let (outputs, proof) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+		distaff::execute(program, &inputs, num_outputs, options)
+	})).map_err(|_| "The program can not be executed on these inputs".to_string())?;
+	let proof = bincode::serialize(&proof).map_err(|e| e.to_string())?;
+	Ok((outputs, proof))
+}
+
+/// Proof options with the blake3 hash function.
+pub fn proof_options(extension_factor: usize, num_queries: usize, grinding_factor: u32) -> ProofOptions {
+	ProofOptions::new(extension_factor, num_queries, grinding_factor, distaff::crypto::hash::blake3)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const SOURCE: &str = "begin push.3 push.5 add end";
+
+	#[test]
+	fn should_check_program_hashes() {
+		let hash = program_hash(SOURCE).unwrap();
+		assert_eq!(check_program_hash(SOURCE, &hash), Ok(true));
+		assert_eq!(check_program_hash("begin push.3 push.5 mul end", &hash), Ok(false));
+		assert!(check_program_hash("begin push.3", &hash).is_err());
+	}
+
+	#[test]
+	fn compiled_hash_should_verify_proofs_of_the_program() {
+		let program = compile(SOURCE).unwrap();
+		let (outputs, proof) = prove(&program, &[], (&[], &[]), 1, &ProofOptions::default()).unwrap();
+		assert_eq!(outputs, vec![8]);
+		let hash = program_hash(SOURCE).unwrap();
+		assert_eq!(crate::verify_with(crate::Backend::Distaff, &hash, &[], &outputs, &proof), Ok(true));
+		assert_eq!(crate::verify_with(crate::Backend::Distaff, &[0u8; 32], &[], &outputs, &proof), Err(crate::VerifyError::InvalidProof));
+	}
+}
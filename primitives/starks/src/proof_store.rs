@@ -0,0 +1,127 @@
+//! A store of proofs kept by the node in a local directory, so that networks without access
+//! to IPFS can still create and verify tasks.
+//!
+//! Proofs are content addressed: the id of a proof is the hex encoded blake2 hash of its bytes,
+//! and it is the file name it is stored under. The store is opened once by the node with `open`,
+//! then read by the offchain workers through `Starks::fetch_local_proof`, and written through
+//! the `verifier_putProof` RPC.
+
+use std::{fs, io, path::{Path, PathBuf}};
+use once_cell::sync::OnceCell;
+use crate::MAX_PROOF_SIZE;
+
+static STORE: OnceCell<ProofStore> = OnceCell::new();
+
+/// A content addressed store of proofs in a directory.
+#[derive(Clone, Debug)]
+pub struct ProofStore {
+	dir: PathBuf,
+}
+
+impl ProofStore {
+	/// Open the store in `dir`, creating the directory if needed.
+	pub fn new(dir: &Path) -> io::Result<Self> {
+		fs::create_dir_all(dir)?;
+		Ok(Self { dir: dir.to_path_buf() })
+	}
+
+	/// The id `proof` is stored under.
+	pub fn id_of(proof: &[u8]) -> String {
+		hex::encode(sp_core::hashing::blake2_256(proof))
+	}
+
+	/// Store `proof`, returning its id.
+	pub fn put(&self, proof: &[u8]) -> io::Result<String> {
+		if proof.len() > MAX_PROOF_SIZE {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "The proof is larger than MAX_PROOF_SIZE"));
+		}
+		let id = Self::id_of(proof);
+		let path = self.dir.join(&id);
+		if !path.exists() {
+			// Write to a temporary file first, so that a proof is never read half written.
+			let partial = self.dir.join(format!("{}.partial", id));
+			fs::write(&partial, proof)?;
+			fs::rename(&partial, &path)?;
+		}
+		Ok(id)
+	}
+
+	/// The proof stored under `id`, if any. A file which does not hash to its id is an error.
+	pub fn get(&self, id: &str) -> io::Result<Option<Vec<u8>>> {
+		// Only ids are looked up, so that no path can be read out of the directory.
+		if id.len() != 64 || !id.bytes().all(|b| b.is_ascii_hexdigit()) {
+			return Ok(None);
+		}
+		let id = id.to_ascii_lowercase();
+		let proof = match fs::read(self.dir.join(&id)) {
+			Ok(proof) => proof,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+			Err(e) => return Err(e),
+		};
+		if Self::id_of(&proof) != id {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, format!("The proof {} does not match its id", id)));
+		}
+		Ok(Some(proof))
+	}
+}
+
+/// Open the store of the node in `dir`. Only the first store opened is used.
+pub fn open(dir: &Path) -> io::Result<&'static ProofStore> {
+	if let Some(store) = STORE.get() {
+		return Ok(store);
+	}
+	let store = ProofStore::new(dir)?;
+	Ok(STORE.get_or_init(|| store))
+}
+
+/// The store of the node, if it is opened.
+pub fn store() -> Option<&'static ProofStore> {
+	STORE.get()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn should_put_and_get_proofs() {
+		let dir = tempfile::tempdir().unwrap();
+		let store = ProofStore::new(dir.path()).unwrap();
+		let id = store.put(b"proof").unwrap();
+		assert_eq!(id, ProofStore::id_of(b"proof"));
+		assert_eq!(store.get(&id).unwrap(), Some(b"proof".to_vec()));
+		assert_eq!(store.get(&id.to_ascii_uppercase()).unwrap(), Some(b"proof".to_vec()));
+		// storing it again keeps the same id
+		assert_eq!(store.put(b"proof").unwrap(), id);
+		assert_eq!(store.get(&ProofStore::id_of(b"another proof")).unwrap(), None);
+		assert!(store.put(&vec![0u8; MAX_PROOF_SIZE + 1]).is_err());
+	}
+
+	#[test]
+	fn should_only_look_up_ids() {
+		let dir = tempfile::tempdir().unwrap();
+		let store = ProofStore::new(&dir.path().join("store")).unwrap();
+		fs::write(dir.path().join("secret"), b"secret").unwrap();
+		// ids of the right length which are paths, and malformed ids
+		let ids = vec![
+			format!("../{}", "0".repeat(61)),
+			format!("/{}", "0".repeat(63)),
+			"../secret".to_string(),
+			"0".repeat(63),
+			"g".repeat(64),
+			String::new(),
+		];
+		for id in &ids {
+			assert_eq!(store.get(id).unwrap(), None, "{}", id);
+		}
+	}
+
+	#[test]
+	fn should_refuse_tampered_proofs() {
+		let dir = tempfile::tempdir().unwrap();
+		let store = ProofStore::new(dir.path()).unwrap();
+		let id = store.put(b"proof").unwrap();
+		fs::write(dir.path().join(&id), b"tampered").unwrap();
+		assert_eq!(store.get(&id).unwrap_err().kind(), io::ErrorKind::InvalidData);
+	}
+}
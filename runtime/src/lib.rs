@@ -34,7 +34,7 @@ pub use frame_system::Call as SystemCall;
 pub use sp_runtime::{Permill, Perbill};
 use sp_runtime::generic::Era;
 pub use frame_support::{
-	construct_runtime, parameter_types, StorageValue,
+	construct_runtime, parameter_types, StorageValue, PalletId,
 	traits::{KeyOwnerProofSystem, Randomness},
 	weights::{
 		Weight, IdentityFee,
@@ -42,7 +42,7 @@ pub use frame_support::{
 	},
 };
 use pallet_transaction_payment::CurrencyAdapter;
-pub use pallet_starks_verifier::crypto::AuthorityId as VerifierId;
+pub use pallet_starks_verifier::{crypto::AuthorityId as VerifierId, Backend, TaskInfo};
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -104,12 +104,15 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// The version of the runtime specification. A full node will not attempt to use its native
 	//   runtime in substitute for the on-chain Wasm runtime unless all of `spec_name`,
 	//   `spec_version`, and `authoring_version` are the same between Wasm and native.
-	// This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
-	//   the compatible custom types.
-	spec_version: 100,
+	// This value started at 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
+	//   the compatible custom types. It has to be bumped on every upgrade, or the storage
+	//   migrations of the pallets are not run.
+	spec_version: 101,
 	impl_version: 1,
 	apis: RUNTIME_API_VERSIONS,
-	transaction_version: 1,
+	// Bumped whenever the encoding of a call changes, e.g. `create_task` taking a `backend`,
+	//   an optional `proof` and an optional `priority_fee`.
+	transaction_version: 2,
 };
 
 /// This determines the average expected block time that we are targeting.
@@ -278,6 +281,19 @@ parameter_types! {
 	pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(17);
 	pub const Period: BlockNumber = 10;
 	pub const Offset: BlockNumber = 10;
+	pub const TaskFee: Balance = 10 * ExistentialDeposit::get();
+	pub const VerifierHistoryDepth: u32 = 3;
+	pub const MaxOpenTasks: u32 = 16;
+	pub const MaxOpenClassTasks: u32 = 1024;
+	pub const QuotaWindow: BlockNumber = HOURS;
+	pub const MaxTasksPerWindow: u32 = 32;
+	pub const MaxClassTasksPerWindow: u32 = 4096;
+	pub const MaxBundleSize: u32 = 8;
+	pub const MinSecurityLevel: u32 = 80;
+	// The size of the largest proof `verify_on_chain` is benchmarked with, `proof.txt` of the pallet
+	pub const MaxOnChainProofSize: u32 = 56_247;
+	pub const MaxIndexedProofSize: u32 = 1024 * 1024;
+	pub const VerifierPalletId: PalletId = PalletId(*b"zk/verif");
 }
 
 impl_opaque_keys! {
@@ -369,6 +385,25 @@ impl pallet_starks_verifier::Config for Runtime {
 	type Event = Event;
 	type StorePeriod = StorePeriod;
 	type UnsignedPriority = VerifierPriority;
+	type ValidatorSet = Session;
+	type HistoryDepth = VerifierHistoryDepth;
+	type Currency = Balances;
+	type TaskFee = TaskFee;
+	type PalletId = VerifierPalletId;
+	type OnTaskSettled = ();
+	type OnTaskRejected = ();
+	type MaxOpenTasks = MaxOpenTasks;
+	type MaxOpenClassTasks = MaxOpenClassTasks;
+	type QuotaWindow = QuotaWindow;
+	type MaxTasksPerWindow = MaxTasksPerWindow;
+	type MaxClassTasksPerWindow = MaxClassTasksPerWindow;
+	type MaxBundleSize = MaxBundleSize;
+	type MinSecurityLevel = MinSecurityLevel;
+	type MaxOnChainProofSize = MaxOnChainProofSize;
+	type MaxIndexedProofSize = MaxIndexedProofSize;
+	type WeightInfo = pallet_starks_verifier::weights::SubstrateWeight<Runtime>;
+	type OffchainSignature = Signature;
+	type OffchainPublic = <Signature as Verify>::Signer;
 }
 
 
@@ -389,7 +424,7 @@ construct_runtime!(
 		Grandpa: pallet_grandpa::{Pallet, Call, Storage, Config, Event},
 		TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
-		StarksVerifier: pallet_starks_verifier::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
+		StarksVerifier: pallet_starks_verifier::{Pallet, Call, Storage, Config<T>, Event<T>, ValidateUnsigned},
 	}
 );
 
@@ -543,6 +578,12 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_starks_verifier_rpc_runtime_api::StarksVerifierApi<Block, AccountId, Balance> for Runtime {
+		fn pending_rewards(who: AccountId) -> Balance {
+			StarksVerifier::pending_rewards(who)
+		}
+	}
+
 	impl pallet_transaction_payment_rpc_runtime_api::TransactionPaymentApi<Block, Balance> for Runtime {
 		fn query_info(
 			uxt: <Block as BlockT>::Extrinsic,
@@ -558,6 +599,14 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[cfg(feature = "try-runtime")]
+	impl frame_try_runtime::TryRuntime<Block> for Runtime {
+		fn on_runtime_upgrade() -> Result<(Weight, Weight), sp_runtime::RuntimeString> {
+			let weight = Executive::try_runtime_upgrade()?;
+			Ok((weight, BlockWeights::get().max_block))
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(
@@ -588,6 +637,7 @@ impl_runtime_apis! {
 			add_benchmark!(params, batches, pallet_balances, Balances);
 			add_benchmark!(params, batches, pallet_timestamp, Timestamp);
 			add_benchmark!(params, batches, pallet_template, TemplateModule);
+			add_benchmark!(params, batches, pallet_starks_verifier, StarksVerifier);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)